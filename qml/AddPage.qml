@@ -11,12 +11,37 @@ Page {
         d.refresh();
     }
 
+    function editApp(url, name, themeColor, iconUrl, patterns, version, nextVersion, containerOptions, packageName, iconStyle, customIcon) {
+        d.loadDefaults();
+        d.editing = true;
+        d.version = version;
+        d.nextVersion = nextVersion;
+        urlField.text = url;
+        nameField.text = name;
+        colorField.text = themeColor;
+        d.iconUrl = iconUrl;
+        d.customIcon = customIcon;
+        var list = patterns.split(",");
+        for (var i=0; i<list.length; ++i) {
+            if (list[i] !== "") {
+                urlPatterns.add(list[i]);
+            }
+        }
+        d.loadContainerOptions(JSON.parse(containerOptions));
+        packageNameField.text = packageName;
+        d.loadIconStyle(JSON.parse(iconStyle));
+    }
+
     visible: false
 
     onVisibleChanged: {
         if (visible) {
-            d.loadDefaults();
+            if (!d.editing) {
+                d.loadDefaults();
+            }
             urlField.forceActiveFocus();
+        } else {
+            d.editing = false;
         }
     }
 
@@ -102,6 +127,13 @@ Page {
                     width: parent.width
                     spacing: Suru.units.dp(8)
 
+                    ProgressBar {
+                        Layout.fillWidth: true
+                        visible: scraper.busy
+                        indeterminate: scraper.progress < 0
+                        value: Math.max(scraper.progress, 0)
+                    }
+
                     Rectangle {
                         id: failLoadBox
 
@@ -128,6 +160,23 @@ Page {
                                 color: Suru.color(Suru.Red)
                             }
 
+                            Label {
+                                readonly property var hints: ({
+                                    "dns": "Check the address and your internet connection.",
+                                    "connect": "Check your internet connection.",
+                                    "tls": "The certificate of the site is not trusted.",
+                                    "not_found": "Check the address for typos.",
+                                    "not_html": "Enter the address of a web page, not of a file.",
+                                    "server_error": "The site has problems, try again later.",
+                                    "timeout": "The site has problems, try again later."
+                                })
+
+                                width: parent.width
+                                visible: text !== ""
+                                text: hints[scraper.errorKind] || ""
+                                wrapMode: Text.WordWrap
+                            }
+
                             RowLayout {
                                 width: parent.width
 
@@ -136,6 +185,7 @@ Page {
                                 }
 
                                 Button {
+                                    visible: scraper.errorKind !== "invalid_url"
                                     text: "Refresh"
                                     onClicked: {
                                         d.refresh();
@@ -145,6 +195,46 @@ Page {
                         }
                     }
 
+                    RowLayout {
+                        Layout.fillWidth: true
+                        visible: scraper.resolvedUrl !== "" && !scraper.busy
+                                 && urlField.displayText !== scraper.resolvedUrl
+
+                        Label {
+                            Layout.fillWidth: true
+                            text: "The site prefers to be opened as %1".arg(scraper.resolvedUrl)
+                            wrapMode: Text.WrapAnywhere
+                        }
+
+                        Button {
+                            text: "Use"
+                            onClicked: setUrl(scraper.resolvedUrl)
+                        }
+                    }
+
+                    Label {
+                        Layout.fillWidth: true
+                        visible: scraper.fallbackUrl !== "" && !scraper.busy
+                        text: "The site does not support secure connections, it will be opened as %1".arg(scraper.fallbackUrl)
+                        wrapMode: Text.WrapAnywhere
+                        color: Suru.color(Suru.Orange)
+                    }
+
+                    Label {
+                        Layout.fillWidth: true
+                        visible: text !== "" && !scraper.busy
+                        text: scraper.warnings ? scraper.warnings.join("\n") : ""
+                        wrapMode: Text.WrapAnywhere
+                        color: Suru.color(Suru.Orange)
+                    }
+
+                    Label {
+                        Layout.fillWidth: true
+                        visible: d.editing
+                        text: "Editing version %1, it will be rebuilt as version %2.".arg(d.version).arg(d.nextVersion)
+                        wrapMode: Text.WordWrap
+                    }
+
                     GridLayout {
                         Layout.fillWidth: true
 
@@ -160,6 +250,7 @@ Page {
                             id: nameField
                             Layout.fillWidth: true
                             placeholderText: "Web app name"
+                            onTextChanged: previewTimer.restart()
                         }
 
                         Label {
@@ -185,6 +276,7 @@ Page {
                                 validator: RegExpValidator {
                                     regExp: /^#(?:[0-9a-fA-F]{3}){1,2}$/
                                 }
+                                onTextChanged: previewTimer.restart()
                             }
                         }
 
@@ -200,6 +292,8 @@ Page {
                                 id: iconImage
 
                                 anchors.fill: parent
+                                source: appModel.iconPreview !== "" ? appModel.iconPreview
+                                                                    : d.iconUrl !== "" ? Qt.resolvedUrl(d.iconUrl) : ""
                                 sourceSize.width: Suru.units.gu(8)
                                 sourceSize.height: Suru.units.gu(8)
 
@@ -210,6 +304,103 @@ Page {
                             }
 
                         }
+
+                        Item { implicitWidth: 1 }
+
+                        RowLayout {
+                            Layout.fillWidth: true
+
+                            Button {
+                                text: "Choose image"
+                                onClicked: iconPickerDialog.open()
+                            }
+
+                            Button {
+                                visible: d.customIcon
+                                text: "Use site icon"
+                                onClicked: {
+                                    d.customIcon = false;
+                                    d.iconUrl = scraper.iconUrl;
+                                }
+                            }
+                        }
+
+                        Item {
+                            implicitWidth: 1
+                            visible: iconErrorLabel.visible
+                        }
+
+                        Label {
+                            id: iconErrorLabel
+                            Layout.fillWidth: true
+                            visible: false
+                            wrapMode: Text.WordWrap
+                            color: Suru.color(Suru.Red)
+                        }
+
+                        Item {
+                            implicitWidth: 1
+                            visible: iconWarningLabel.visible
+                        }
+
+                        Label {
+                            id: iconWarningLabel
+                            Layout.fillWidth: true
+                            visible: appModel.iconWarning !== ""
+                            text: appModel.iconWarning
+                            wrapMode: Text.WordWrap
+                            color: Suru.color(Suru.Orange)
+                        }
+
+                        Item { implicitWidth: 1 }
+
+                        ColumnLayout {
+                            Layout.fillWidth: true
+
+                            RowLayout {
+                                Layout.fillWidth: true
+
+                                CheckBox {
+                                    id: iconBackgroundCheck
+                                    text: "Background"
+                                    onCheckedChanged: previewTimer.restart()
+                                }
+
+                                TextField {
+                                    id: iconBackgroundField
+                                    Layout.fillWidth: true
+                                    enabled: iconBackgroundCheck.checked
+                                    placeholderText: "Theme color"
+                                    validator: RegExpValidator {
+                                        regExp: /^(#(?:[0-9a-fA-F]{3}){1,2})?$/
+                                    }
+                                    onTextChanged: previewTimer.restart()
+                                }
+                            }
+
+                            RowLayout {
+                                Layout.fillWidth: true
+
+                                Label {
+                                    text: "Padding"
+                                }
+
+                                Slider {
+                                    id: iconPaddingSlider
+                                    Layout.fillWidth: true
+                                    from: 0
+                                    to: 40
+                                    stepSize: 1
+                                    onValueChanged: previewTimer.restart()
+                                }
+                            }
+
+                            CheckBox {
+                                id: iconRoundedCheck
+                                text: "Rounded"
+                                onCheckedChanged: previewTimer.restart()
+                            }
+                        }
                     }
 
                     RowLayout {
@@ -240,10 +431,12 @@ Page {
 
                         delegate: Item {
                             width: parent.width
-                            height: Suru.units.gu(5)
+                            height: patternColumn.height + Suru.units.gu(1)
 
-                            RowLayout {
-                                anchors.fill: parent
+                            ColumnLayout {
+                                id: patternColumn
+
+                                width: parent.width
 
                                 TextField {
                                     Layout.fillWidth: true
@@ -258,9 +451,214 @@ Page {
                                         }
                                     }
                                 }
+
+                                Label {
+                                    Layout.fillWidth: true
+                                    visible: !model.valid
+                                    text: model.error
+                                    wrapMode: Text.WordWrap
+                                    color: Suru.color(Suru.Red)
+                                }
+                            }
+                        }
+                    }
+
+                    Label {
+                        Layout.fillWidth: true
+                        visible: suggestionsRepeater.count > 0
+                        text: "Suggested patterns"
+                        font.bold: true
+                    }
+
+                    Repeater {
+                        id: suggestionsRepeater
+
+                        model: d.editing ? [] : scraper.suggestedUrlPatterns
+
+                        delegate: RowLayout {
+                            Layout.fillWidth: true
+
+                            Column {
+                                Layout.fillWidth: true
+
+                                Label {
+                                    width: parent.width
+                                    text: modelData
+                                    elide: Text.ElideRight
+                                }
+
+                                Label {
+                                    width: parent.width
+                                    text: scraper.suggestedUrlPatternReasons[index]
+                                    wrapMode: Text.WordWrap
+                                    color: Suru.secondaryForegroundColor
+                                }
+                            }
+
+                            Button {
+                                id: addSuggestionButton
+                                text: "Add"
+                                onClicked: {
+                                    urlPatterns.add(modelData);
+                                    addSuggestionButton.enabled = false;
+                                }
                             }
                         }
                     }
+
+                    TextField {
+                        id: testUrlField
+
+                        Layout.fillWidth: true
+                        placeholderText: "Try a url, e.g. a login page"
+                        inputMethodHints: Qt.ImhUrlCharactersOnly
+                    }
+
+                    Label {
+                        readonly property int match: {
+                            // Depend on these so that the result updates when the patterns change
+                            urlPatterns.count;
+                            urlPatterns.valid;
+                            return urlPatterns.testUrl(testUrlField.displayText);
+                        }
+
+                        Layout.fillWidth: true
+                        visible: testUrlField.displayText !== ""
+                        wrapMode: Text.WordWrap
+                        text: match >= 0
+                              ? "Stays in the app, matches pattern %1".arg(match + 1)
+                              : "Opens in the browser, no pattern matches"
+                        color: match >= 0 ? Suru.color(Suru.Green) : Suru.color(Suru.Orange)
+                    }
+
+                    RowLayout {
+                        Layout.fillWidth: true
+
+                        Label {
+                            text: "Advanced"
+                            font.bold: true
+                        }
+
+                        Item { Layout.fillWidth: true }
+
+                        Button {
+                            text: advancedOptions.visible ? "Hide" : "Show"
+                            onClicked: advancedOptions.visible = !advancedOptions.visible
+                        }
+                    }
+
+                    ColumnLayout {
+                        id: advancedOptions
+
+                        Layout.fillWidth: true
+                        visible: false
+                        spacing: Suru.units.dp(8)
+
+                        CheckBox {
+                            id: sessionCookiesCheck
+                            text: "Store session cookies"
+                            checked: true
+                        }
+
+                        CheckBox {
+                            id: fullscreenCheck
+                            text: "Fullscreen"
+                        }
+
+                        CheckBox {
+                            id: backForwardCheck
+                            text: "Show back and forward buttons"
+                        }
+
+                        CheckBox {
+                            id: addressbarCheck
+                            text: "Show address bar"
+                        }
+
+                        CheckBox {
+                            id: overlayCheck
+                            text: "Open external links in an overlay instead of the browser"
+                        }
+
+                        CheckBox {
+                            id: localContentCheck
+                            text: "Allow local content to access remote urls"
+                        }
+
+                        CheckBox {
+                            id: mediaHubCheck
+                            text: "Play audio through media-hub"
+                        }
+
+                        RowLayout {
+                            Layout.fillWidth: true
+
+                            Label {
+                                text: "User agent"
+                            }
+
+                            ComboBox {
+                                id: userAgentPresetBox
+
+                                readonly property string preset: model.get(currentIndex).name
+
+                                Layout.fillWidth: true
+                                textRole: "label"
+                                model: ListModel {
+                                    ListElement { name: "default"; label: "Default" }
+                                    ListElement { name: "mobile_chrome"; label: "Mobile Chrome" }
+                                    ListElement { name: "mobile_firefox"; label: "Mobile Firefox" }
+                                    ListElement { name: "desktop"; label: "Desktop" }
+                                    ListElement { name: "custom"; label: "Custom" }
+                                }
+                                onActivated: d.refresh()
+
+                                function selectPreset(name) {
+                                    for (var i=0; i<model.count; ++i) {
+                                        if (model.get(i).name === name) {
+                                            currentIndex = i;
+                                            return;
+                                        }
+                                    }
+                                    currentIndex = 0;
+                                }
+                            }
+                        }
+
+                        TextField {
+                            id: userAgentField
+                            Layout.fillWidth: true
+                            visible: userAgentPresetBox.preset === "custom"
+                            placeholderText: "User agent"
+                            onEditingFinished: d.refresh()
+                        }
+
+                        TextField {
+                            id: searchPathField
+                            Layout.fillWidth: true
+                            placeholderText: "Webapp model search path"
+                        }
+
+                        TextField {
+                            id: packageNameField
+                            Layout.fillWidth: true
+                            placeholderText: urlField.displayText !== ""
+                                             ? appModel.resolvePackageName(urlField.displayText, "")
+                                             : "Package name"
+                        }
+
+                        Label {
+                            readonly property string clash: urlField.displayText !== ""
+                                                            ? appModel.packageNameClash(urlField.displayText, packageNameField.text)
+                                                            : ""
+
+                            Layout.fillWidth: true
+                            visible: clash !== ""
+                            text: "This name is already used by the shortcut for %1".arg(clash)
+                            wrapMode: Text.WordWrap
+                            color: Suru.color(Suru.Red)
+                        }
+                    }
                 }
             }
         }
@@ -284,14 +682,14 @@ Page {
 
             Button {
                 text: "Create"
-                enabled: urlField.text !== "" && nameField.text !== ""
+                enabled: urlField.text !== "" && nameField.text !== "" && urlPatterns.valid
                 onClicked: {
                     addDialog.open();
                     appModel.create(
-                        urlField.text,
+                        scraper.fallbackUrl !== "" ? scraper.fallbackUrl : urlField.text,
                         nameField.text,
                         colorField.text,
-                        scraper.iconUrl,
+                        d.iconUrl,
                         urlPatterns.getPatternsString()
                     );
                 }
@@ -314,6 +712,17 @@ Page {
                 anchors.centerIn: parent
                 running: true
             }
+
+            ProgressBar {
+                anchors {
+                    left: parent.left
+                    right: parent.right
+                    bottom: parent.bottom
+                }
+                visible: appModel.progress !== 0
+                indeterminate: appModel.progress < 0
+                value: Math.max(appModel.progress, 0)
+            }
         }
 
         standardButtons: Dialog.NoButton
@@ -321,9 +730,29 @@ Page {
         closePolicy: Dialog.NoAutoClose
     }
 
+    Dialog {
+        id: errorDialog
+
+        x: (parent.width - width) / 2
+        y: (parent.height - height) / 2
+        width: Math.min(parent.width - Suru.units.gu(4), Suru.units.gu(50))
+
+        title: "Failed to create shortcut"
+        contentItem: Label {
+            text: appModel.errorString
+            wrapMode: Text.WordWrap
+            color: Suru.color(Suru.Red)
+        }
+
+        standardButtons: Dialog.Ok
+        modal: true
+    }
+
     InstallDialog {
         id: installDialog
 
+        warning: appModel.warningString
+
         x: (parent.width - width) / 2
         y: (parent.height - height) / 2
 
@@ -335,6 +764,25 @@ Page {
         closePolicy: Dialog.NoAutoClose
     }
 
+    IconPickerDialog {
+        id: iconPickerDialog
+
+        contentWidth: parent.width - Suru.units.gu(16)
+        contentHeight: parent.height - Suru.units.gu(16)
+
+        onImagePicked: {
+            iconErrorLabel.visible = false;
+            appModel.importIcon(url);
+        }
+    }
+
+    Timer {
+        id: previewTimer
+        interval: 300
+        repeat: false
+        onTriggered: appModel.updateIconPreview(urlField.text, nameField.text, d.iconUrl, colorField.text)
+    }
+
     Timer {
         id: scrapeTimer
         interval: 800
@@ -345,12 +793,55 @@ Page {
     QtObject {
         id: d
 
+        // Set while an existing shortcut is edited, scraping must not overwrite its fields
+        property bool editing: false
+        property string version: ""
+        property string nextVersion: ""
+        property string iconUrl: ""
+        // Set once the user picked an image, the scraped icon must not replace it
+        property bool customIcon: false
+
+        onIconUrlChanged: previewTimer.restart()
+
         function loadDefaults() {
+            editing = false;
             nameField.text = "";
             colorField.text = "#ffffff";
             urlField.text = "";
-            iconImage.source = "";
+            iconUrl = "";
+            customIcon = false;
+            iconErrorLabel.visible = false;
             urlPatterns.clear();
+            packageNameField.text = "";
+            loadIconStyle({});
+            loadContainerOptions({ "store_session_cookies": true });
+        }
+
+        function loadContainerOptions(options) {
+            sessionCookiesCheck.checked = !!options.store_session_cookies;
+            fullscreenCheck.checked = !!options.fullscreen;
+            backForwardCheck.checked = !!options.enable_back_forward;
+            addressbarCheck.checked = !!options.enable_addressbar;
+            overlayCheck.checked = !!options.open_external_url_in_overlay;
+            localContentCheck.checked = !!options.local_content_can_access_remote_urls;
+            mediaHubCheck.checked = !!options.enable_media_hub_audio;
+            // Presets are stored by name, custom user agents as { "custom": "..." }
+            var userAgent = options.user_agent || "default";
+            if (typeof userAgent === "string") {
+                userAgentPresetBox.selectPreset(userAgent);
+                userAgentField.text = "";
+            } else {
+                userAgentPresetBox.selectPreset("custom");
+                userAgentField.text = userAgent.custom || "";
+            }
+            searchPathField.text = options.webapp_model_search_path || "";
+        }
+
+        function loadIconStyle(style) {
+            iconBackgroundCheck.checked = !!style.background;
+            iconBackgroundField.text = style.background_color || "";
+            iconPaddingSlider.value = style.padding || 0;
+            iconRoundedCheck.checked = style.mask === "squircle";
         }
 
         function refresh() {
@@ -367,17 +858,56 @@ Page {
     AppModel {
         id: appModel
 
+        storeSessionCookies: sessionCookiesCheck.checked
+        fullscreen: fullscreenCheck.checked
+        enableBackForward: backForwardCheck.checked
+        enableAddressbar: addressbarCheck.checked
+        openExternalUrlInOverlay: overlayCheck.checked
+        localContentCanAccessRemoteUrls: localContentCheck.checked
+        enableMediaHubAudio: mediaHubCheck.checked
+        userAgentPreset: userAgentPresetBox.preset
+        userAgent: userAgentField.text
+        webappModelSearchPath: searchPathField.text
+        packageName: packageNameField.text
+        iconBackground: iconBackgroundCheck.checked
+        iconBackgroundColor: iconBackgroundField.text
+        iconPadding: iconPaddingSlider.value
+        iconMask: iconRoundedCheck.checked ? "squircle" : "none"
+        customIcon: d.customIcon
+
+        onIconImported: {
+            d.customIcon = true;
+            d.iconUrl = iconUrl;
+        }
+
+        onIconImportFailed: {
+            iconErrorLabel.text = "The image could not be used as icon: %1".arg(error);
+            iconErrorLabel.visible = true;
+        }
+
         onCreated: {
             addDialog.close()
             installDialog.open();
         }
+
+        onFailed: {
+            addDialog.close();
+            errorDialog.open();
+        }
     }
 
     WebScraper {
         id: scraper
         url: urlField.displayText
+        userAgentPreset: userAgentPresetBox.preset
+        userAgent: userAgentField.text
         onScraped: {
-            if (siteName != "" && nameField.text !== siteName) {
+            if (d.editing) {
+                return;
+            }
+            if (shortName != "" && nameField.text !== shortName) {
+                nameField.text = shortName;
+            } else if (siteName != "" && nameField.text !== siteName) {
                 nameField.text = siteName;
             } else if (title !== "" && nameField.text !== title) {
                 nameField.text = title;
@@ -385,7 +915,9 @@ Page {
             if (themeColor != "" && colorField.validator.regExp.test(themeColor)) {
                 colorField.text = themeColor;
             }
-            iconImage.source = iconUrl !== "" ? Qt.resolvedUrl(iconUrl) : "";
+            if (!d.customIcon) {
+                d.iconUrl = iconUrl;
+            }
             if (defaultUrlPatterns !== []) {
                 urlPatterns.clear();
                 for (var i=0; i<defaultUrlPatterns.length; ++i) {
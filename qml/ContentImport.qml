@@ -5,6 +5,7 @@ QtObject {
     id: contentImport
 
     signal urlRequested(url url)
+    signal clickRequested(url url)
 
     property var d: QtObject {
         id: d
@@ -14,12 +15,20 @@ QtObject {
                 if (transfer.items.length >= 1) {
                     contentImport.urlRequested(transfer.items[0].url);
                 }
+            } else if (transfer.contentType === ContentType.Documents) {
+                for (var i=0; i<transfer.items.length; ++i) {
+                    var url = transfer.items[i].url;
+                    if (url.toString().match(/\.click$/)) {
+                        contentImport.clickRequested(url);
+                    }
+                }
             }
         }
 
         property var conn: Connections {
             target: ContentHub
             onShareRequested: d.startImport(transfer)
+            onImportRequested: d.startImport(transfer)
         }
     }
 }
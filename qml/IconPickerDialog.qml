@@ -0,0 +1,58 @@
+import QtQuick 2.0
+import QtQuick.Layouts 1.0
+import QtQuick.Controls 2.2
+import QtQuick.Controls.Suru 2.2
+import Ubuntu.Content 1.3
+import "."
+
+Dialog {
+    id: dialog
+
+    signal imagePicked(url url)
+
+    x: (parent.width - width) / 2
+    y: (parent.height - height) / 2
+
+    title: "Choose icon"
+
+    contentItem: Item {
+        implicitWidth: dialog.contentWidth
+        implicitHeight: dialog.contentHeight
+
+        ContentPeerPicker {
+            id: picker
+
+            property var activeTransfer
+
+            anchors.fill: parent
+            showTitle: false
+            contentType: ContentType.Pictures
+            handler: ContentHandler.Source
+
+            onPeerSelected: {
+                peer.selectionType = ContentTransfer.Single;
+                picker.activeTransfer = peer.request();
+                picker.activeTransfer.stateChanged.connect(function() {
+                    if (picker.activeTransfer.state === ContentTransfer.Charged) {
+                        if (picker.activeTransfer.items.length > 0) {
+                            dialog.imagePicked(picker.activeTransfer.items[0].url);
+                        }
+                        picker.activeTransfer.finalize();
+                        dialog.close();
+                    }
+                })
+            }
+
+            onCancelPressed: dialog.close()
+        }
+
+        ContentTransferHint {
+            id: transferHint
+            anchors.fill: parent
+            activeTransfer: picker.activeTransfer
+        }
+    }
+
+    standardButtons: Dialog.NoButton
+    modal: true
+}
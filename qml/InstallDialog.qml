@@ -9,6 +9,8 @@ Dialog {
     id: dialog
 
     property url url: Qt.resolvedUrl("file:///home/phablet/.cache/webber.timsueberkrueb/click-build/shortcut.click")
+    // Problems that did not stop the package from being created
+    property string warning: ""
 
     x: (parent.width - width) / 2
     y: (parent.height - height) / 2
@@ -19,12 +21,31 @@ Dialog {
         implicitWidth: dialog.contentWidth
         implicitHeight: dialog.contentHeight
 
+        Label {
+            id: warningLabel
+            anchors {
+                left: parent.left
+                right: parent.right
+                top: parent.top
+            }
+            visible: dialog.warning !== ""
+            height: visible ? implicitHeight : 0
+            text: dialog.warning
+            wrapMode: Text.WordWrap
+            color: Suru.color(Suru.Orange)
+        }
+
         ContentPeerPicker {
             id: picker
 
             property var activeTransfer
 
-            anchors.fill: parent
+            anchors {
+                left: parent.left
+                right: parent.right
+                top: warningLabel.bottom
+                bottom: parent.bottom
+            }
             showTitle: false
             contentType: ContentType.All
             handler: ContentHandler.Destination
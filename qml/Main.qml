@@ -1,6 +1,6 @@
 import QtQuick 2.0
 import QtQuick.Layouts 1.0
-import QtQuick.Controls 2.0
+import QtQuick.Controls 2.2
 import QtQuick.Controls.Suru 2.2
 import Webber 1.0
 import "."
@@ -28,6 +28,8 @@ ApplicationWindow {
         }
 
         initialItem: Page {
+            StackView.onActivated: library.reload()
+
             header: ToolBar {
                 RowLayout {
                     anchors.fill: parent
@@ -54,11 +56,85 @@ ApplicationWindow {
 
                 spacing: Suru.units.dp(8)
 
-                RowLayout {
+                Label {
+                    Layout.fillWidth: true
+                    visible: library.errorString !== ""
+                    text: library.errorString
+                    wrapMode: Text.WordWrap
+                    color: Suru.color(Suru.Red)
+                }
+
+                Label {
                     Layout.fillWidth: true
+                    visible: library.count === 0
+                    text: "No shortcuts yet. Tap \"Add\" to create one."
+                    wrapMode: Text.WordWrap
+                    horizontalAlignment: Text.AlignHCenter
                 }
 
-                Item { Layout.fillHeight: true }
+                ListView {
+                    id: libraryView
+
+                    Layout.fillWidth: true
+                    Layout.fillHeight: true
+
+                    model: library
+                    clip: true
+
+                    delegate: ItemDelegate {
+                        width: parent.width
+                        height: Suru.units.gu(7)
+
+                        onClicked: {
+                            stackView.push(addPage);
+                            addPage.editApp(model.url, model.name, model.themeColor,
+                                            model.iconUrl, model.urlPatterns,
+                                            model.version, model.nextVersion,
+                                            model.containerOptions, model.packageName,
+                                            model.iconStyle, model.customIcon);
+                        }
+
+                        contentItem: RowLayout {
+                            spacing: Suru.units.gu(2)
+
+                            Rectangle {
+                                implicitWidth: Suru.units.gu(5)
+                                implicitHeight: Suru.units.gu(5)
+                                radius: Suru.units.dp(4)
+                                color: model.themeColor
+
+                                Image {
+                                    anchors.fill: parent
+                                    sourceSize.width: width
+                                    sourceSize.height: height
+                                    source: model.iconUrl
+                                }
+                            }
+
+                            Column {
+                                Layout.fillWidth: true
+
+                                Label {
+                                    width: parent.width
+                                    text: model.name
+                                    elide: Text.ElideRight
+                                }
+
+                                Label {
+                                    width: parent.width
+                                    text: model.url + " · " + model.version
+                                    elide: Text.ElideRight
+                                    color: Suru.secondaryForegroundColor
+                                }
+                            }
+
+                            IconButton {
+                                iconName: "delete"
+                                onClicked: library.remove(index)
+                            }
+                        }
+                    }
+                }
             }
         }
     }
@@ -74,11 +150,18 @@ ApplicationWindow {
 
     AddPage { id: addPage }
 
+    LibraryModel { id: library }
+
     ContentImport {
         onUrlRequested: {
             stackView.push(addPage);
             addPage.setUrl(url);
         }
+
+        onClickRequested: {
+            stackView.pop(null);
+            library.importClick(url.toString());
+        }
     }
 
     Component.onCompleted: {
@@ -0,0 +1,389 @@
+use std::fs;
+#[cfg(any(feature = "gui", test))]
+use std::io::Read;
+use std::io::{self, Write};
+use std::path::Path;
+
+#[cfg(any(feature = "gui", test))]
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+
+const BLOCK_SIZE: usize = 512;
+// Fixed timestamp so that building the same package twice yields the same archive
+const MTIME: u64 = 0;
+// Shortcuts only hold a few small files, anything bigger is not one of ours
+#[cfg(any(feature = "gui", test))]
+const MAX_UNPACKED_SIZE: u64 = 32 * 1024 * 1024;
+
+pub fn write_tar_gz(filepath: &Path, dir: &Path) -> io::Result<()> {
+    let file = fs::File::create(filepath)?;
+    let encoder = GzEncoder::new(file, Compression::default());
+    let mut builder = TarBuilder::new(encoder);
+    builder.append_dir("./")?;
+    append_dir_contents(&mut builder, dir, ".")?;
+    builder.finish()?.finish()?;
+    Ok(())
+}
+
+// Reads the regular files of a tar.gz archive that `wanted` accepts, with paths normalized to
+// not start with ./
+#[cfg(any(feature = "gui", test))]
+pub fn read_tar_gz<R: Read, F: Fn(&str) -> bool>(
+    reader: R,
+    wanted: F,
+) -> io::Result<Vec<(String, Vec<u8>)>> {
+    let mut reader = GzDecoder::new(reader);
+    let mut files = Vec::new();
+    let mut long_name = None;
+    let mut header = [0; BLOCK_SIZE];
+    let mut unpacked = 0;
+
+    loop {
+        if !read_block(&mut reader, &mut header)? || header.iter().all(|b| *b == 0) {
+            break;
+        }
+        let size = parse_octal(&header[124..136])?;
+        // Skipped entries are decompressed as well, so they count too
+        unpacked += size;
+        if unpacked > MAX_UNPACKED_SIZE {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "The archive is too large",
+            ));
+        }
+        let padding = (BLOCK_SIZE as u64 - size % BLOCK_SIZE as u64) % BLOCK_SIZE as u64;
+
+        let path = match long_name.take() {
+            Some(name) => name,
+            None => {
+                let name = field_str(&header[0..100]);
+                let prefix = field_str(&header[345..500]);
+                if &header[257..262] == b"ustar" && !prefix.is_empty() {
+                    format!("{}/{}", prefix, name)
+                } else {
+                    name
+                }
+            }
+        };
+        let path = path.trim_start_matches("./").trim_start_matches('/');
+        let keep = match header[156] {
+            // GNU extension for paths longer than 100 bytes, applies to the next entry
+            b'L' => true,
+            b'0' | 0 => wanted(path),
+            _ => false,
+        };
+
+        // The size comes from the archive, so the buffer only grows with what is really there
+        let mut data = Vec::new();
+        let read = if keep {
+            (&mut reader).take(size).read_to_end(&mut data)? as u64
+        } else {
+            io::copy(&mut (&mut reader).take(size), &mut io::sink())?
+        };
+        let skipped = io::copy(&mut (&mut reader).take(padding), &mut io::sink())?;
+        if read != size || skipped != padding {
+            return Err(io::ErrorKind::UnexpectedEof.into());
+        }
+
+        match header[156] {
+            b'L' => long_name = Some(field_str(&data)),
+            _ if keep => files.push((path.to_owned(), data)),
+            _ => {}
+        }
+    }
+
+    Ok(files)
+}
+
+#[cfg(any(feature = "gui", test))]
+fn read_block<R: Read>(reader: &mut R, block: &mut [u8]) -> io::Result<bool> {
+    let mut read = 0;
+    while read < block.len() {
+        match reader.read(&mut block[read..])? {
+            0 if read == 0 => return Ok(false),
+            0 => return Err(io::ErrorKind::UnexpectedEof.into()),
+            n => read += n,
+        }
+    }
+    Ok(true)
+}
+
+#[cfg(any(feature = "gui", test))]
+fn field_str(field: &[u8]) -> String {
+    let end = field
+        .iter()
+        .position(|b| *b == 0)
+        .unwrap_or(field.len());
+    String::from_utf8_lossy(&field[..end]).into_owned()
+}
+
+#[cfg(any(feature = "gui", test))]
+fn parse_octal(field: &[u8]) -> io::Result<u64> {
+    let s = field_str(field);
+    let s = s.trim_matches(|c: char| c == ' ' || c == '\0');
+    if s.is_empty() {
+        return Ok(0);
+    }
+    u64::from_str_radix(s, 8).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+}
+
+fn append_dir_contents<W: Write>(
+    builder: &mut TarBuilder<W>,
+    dir: &Path,
+    prefix: &str,
+) -> io::Result<()> {
+    let mut entries = fs::read_dir(dir)?.collect::<io::Result<Vec<_>>>()?;
+    entries.sort_by_key(|entry| entry.file_name());
+
+    for entry in entries {
+        let name = entry.file_name().into_string().map_err(|name| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Non UTF-8 file name: {:?}", name),
+            )
+        })?;
+        let path = format!("{}/{}", prefix, name);
+        let metadata = entry.metadata()?;
+        if metadata.is_dir() {
+            builder.append_dir(&format!("{}/", path))?;
+            append_dir_contents(builder, &entry.path(), &path)?;
+        } else {
+            let data = fs::read(entry.path())?;
+            let mode = if is_executable(&metadata) {
+                0o755
+            } else {
+                0o644
+            };
+            builder.append_file(&path, mode, &data)?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(unix)]
+fn is_executable(metadata: &fs::Metadata) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    metadata.permissions().mode() & 0o111 != 0
+}
+
+#[cfg(not(unix))]
+fn is_executable(_metadata: &fs::Metadata) -> bool {
+    false
+}
+
+// Minimal ustar writer. The `tar` crate normalizes the leading ./ away, which
+// the click tooling expects to be present on every entry.
+struct TarBuilder<W: Write> {
+    writer: W,
+}
+
+impl<W: Write> TarBuilder<W> {
+    fn new(writer: W) -> Self {
+        Self { writer }
+    }
+
+    fn append_dir(&mut self, path: &str) -> io::Result<()> {
+        let header = header(path, 0o755, 0, b'5')?;
+        self.writer.write_all(&header)
+    }
+
+    fn append_file(&mut self, path: &str, mode: u32, data: &[u8]) -> io::Result<()> {
+        let header = header(path, mode, data.len() as u64, b'0')?;
+        self.writer.write_all(&header)?;
+        self.writer.write_all(data)?;
+        let padding = (BLOCK_SIZE - data.len() % BLOCK_SIZE) % BLOCK_SIZE;
+        self.writer.write_all(&vec![0; padding])
+    }
+
+    fn finish(mut self) -> io::Result<W> {
+        self.writer.write_all(&[0; 2 * BLOCK_SIZE])?;
+        Ok(self.writer)
+    }
+}
+
+fn header(path: &str, mode: u32, size: u64, typeflag: u8) -> io::Result<[u8; BLOCK_SIZE]> {
+    let mut header = [0; BLOCK_SIZE];
+
+    let (prefix, name) = split_path(path).ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("Path too long for tar archive: {}", path),
+        )
+    })?;
+    header[0..name.len()].copy_from_slice(name.as_bytes());
+    write_octal(&mut header[100..108], u64::from(mode));
+    write_octal(&mut header[108..116], 0);
+    write_octal(&mut header[116..124], 0);
+    write_octal(&mut header[124..136], size);
+    write_octal(&mut header[136..148], MTIME);
+    header[156] = typeflag;
+    header[257..263].copy_from_slice(b"ustar\0");
+    header[263..265].copy_from_slice(b"00");
+    header[265..269].copy_from_slice(b"root");
+    header[297..301].copy_from_slice(b"root");
+    header[345..345 + prefix.len()].copy_from_slice(prefix.as_bytes());
+
+    // The checksum is calculated with the checksum field filled with spaces
+    header[148..156].copy_from_slice(b"        ");
+    let checksum = header.iter().map(|b| u64::from(*b)).sum::<u64>();
+    write_octal(&mut header[148..155], checksum);
+    header[155] = b' ';
+
+    Ok(header)
+}
+
+fn split_path(path: &str) -> Option<(&str, &str)> {
+    if path.len() <= 100 {
+        return Some(("", path));
+    }
+    // Split at a slash so that the prefix fits in 155 and the name in 100 bytes
+    path.char_indices()
+        .filter(|(_, c)| *c == '/')
+        .map(|(pos, _)| (&path[..pos], &path[pos + 1..]))
+        .find(|(prefix, name)| prefix.len() <= 155 && name.len() <= 100 && !name.is_empty())
+}
+
+// Writes a zero-terminated, zero-padded octal number filling the field
+fn write_octal(field: &mut [u8], value: u64) {
+    let digits = format!("{:0width$o}", value, width = field.len() - 1);
+    let len = field.len();
+    field[..len - 1].copy_from_slice(&digits.as_bytes()[digits.len() - (len - 1)..]);
+    field[len - 1] = 0;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+    use std::process::Command;
+
+    fn long_dir() -> String {
+        format!("{}/{}", "a".repeat(60), "b".repeat(60))
+    }
+
+    // A package directory with a path that only fits with the ustar prefix
+    fn package_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("webber-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(dir.join(long_dir())).unwrap();
+        fs::write(dir.join("manifest.json"), b"{}").unwrap();
+        fs::write(dir.join(long_dir()).join("icon.png"), vec![7; 1000]).unwrap();
+        dir
+    }
+
+    #[test]
+    fn round_trip() {
+        let dir = package_dir("round-trip");
+        let archive = dir.with_extension("tar.gz");
+        write_tar_gz(&archive, &dir).unwrap();
+
+        let files = read_tar_gz(fs::File::open(&archive).unwrap(), |_| true).unwrap();
+        assert_eq!(
+            files,
+            vec![
+                (format!("{}/icon.png", long_dir()), vec![7; 1000]),
+                ("manifest.json".to_owned(), b"{}".to_vec()),
+            ]
+        );
+    }
+
+    fn archive_with_header(size: u64) -> Vec<u8> {
+        let mut tar = Vec::new();
+        let mut builder = TarBuilder::new(&mut tar);
+        builder
+            .append_file("./manifest.json", 0o644, b"{}")
+            .unwrap();
+        // Ends right after the header, whatever size it claims
+        let header = header("./icon.png", 0o644, size, b'0').unwrap();
+        tar.extend_from_slice(&header);
+
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(&tar).unwrap();
+        encoder.finish().unwrap()
+    }
+
+    #[test]
+    fn truncated_entry_is_an_error() {
+        let archive = archive_with_header(1000);
+        let err = read_tar_gz(archive.as_slice(), |_| true).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+    }
+
+    #[test]
+    fn oversized_archive_is_an_error() {
+        // Claims a size of 8 GiB
+        let archive = archive_with_header(0o77_777_777_777);
+        let err = read_tar_gz(archive.as_slice(), |_| true).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn unwanted_entries_are_skipped() {
+        let dir = package_dir("skipped");
+        let archive = dir.with_extension("tar.gz");
+        write_tar_gz(&archive, &dir).unwrap();
+
+        let files = read_tar_gz(fs::File::open(&archive).unwrap(), |path| {
+            path == "manifest.json"
+        })
+        .unwrap();
+        assert_eq!(files, vec![("manifest.json".to_owned(), b"{}".to_vec())]);
+    }
+
+    #[test]
+    fn header_checksum() {
+        let header = header("./manifest.json", 0o644, 2, b'0').unwrap();
+        let checksum = parse_octal(&header[148..156]).unwrap();
+        let mut unsigned = header;
+        unsigned[148..156].copy_from_slice(b"        ");
+        assert_eq!(
+            checksum,
+            unsigned.iter().map(|b| u64::from(*b)).sum::<u64>()
+        );
+    }
+
+    #[test]
+    fn long_path_is_split_at_a_slash() {
+        let path = format!("./{}/icon.png", long_dir());
+        let (prefix, name) = split_path(&path).unwrap();
+        assert_eq!(format!("{}/{}", prefix, name), path);
+        assert!(prefix.len() <= 155 && name.len() <= 100);
+        assert!(split_path(&"a".repeat(101)).is_none());
+    }
+
+    // GNU tar verifies the checksums, the ustar prefix and the leading ./ click expects
+    #[test]
+    fn listed_by_tar() {
+        let dir = package_dir("listed-by-tar");
+        let archive = dir.with_extension("tar.gz");
+        write_tar_gz(&archive, &dir).unwrap();
+
+        let output = match Command::new("tar").arg("-tvzf").arg(&archive).output() {
+            Ok(output) => output,
+            // Nothing to compare with
+            Err(_) => return,
+        };
+        assert!(
+            output.status.success(),
+            "{}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+        let listing = String::from_utf8(output.stdout).unwrap();
+        let paths: Vec<&str> = listing
+            .lines()
+            .map(|line| line.split_whitespace().last().unwrap())
+            .collect();
+        assert_eq!(
+            paths,
+            vec![
+                "./".to_owned(),
+                format!("./{}/", "a".repeat(60)),
+                format!("./{}/", long_dir()),
+                format!("./{}/icon.png", long_dir()),
+                "./manifest.json".to_owned(),
+            ]
+        );
+        assert!(listing.lines().all(|line| line.contains("root/root")));
+    }
+}
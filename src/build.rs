@@ -28,6 +28,11 @@ fn qmake_query(var: &str) -> String {
 }
 
 fn main() {
+    // The command line only build neither needs nor links Qt
+    if std::env::var_os("CARGO_FEATURE_GUI").is_none() {
+        return;
+    }
+
     let qt_include_path = qmake_query("QT_INSTALL_HEADERS");
     let qt_library_path = qmake_query("QT_INSTALL_LIBS");
 
@@ -0,0 +1,209 @@
+use std::cmp::Reverse;
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use reqwest::header::{self, HeaderMap};
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+// Fetched pages, manifests and icons are kept in XDG_CACHE_HOME, every entry as
+// <key>.json with the response metadata and <key>.body, where the key is the md5 of
+// the user agent and the url. Sites serve different markup to different user agents,
+// so switching the user agent must not return what was fetched with another one.
+// Entries are revalidated with ETag/Last-Modified and used as is when offline.
+// Every store prunes entries that were not fetched for MAX_AGE, and the oldest ones
+// while the cache is larger than MAX_SIZE.
+
+// Entries younger than this are used without asking the server, so that scraping again
+// after every edit of the url does not cause any traffic
+const FRESH_SECONDS: u64 = 60;
+const MAX_AGE: Duration = Duration::from_secs(30 * 24 * 60 * 60);
+const MAX_SIZE: u64 = 64 * 1024 * 1024;
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Response {
+    pub url: String,
+    pub status: u16,
+    pub location: Option<String>,
+    pub content_type: Option<String>,
+    pub etag: Option<String>,
+    pub last_modified: Option<String>,
+    // Unix time of the last download or revalidation
+    pub fetched_at: u64,
+    #[serde(skip)]
+    pub body: Vec<u8>,
+}
+
+impl Response {
+    pub fn new(url: &Url, resp: &reqwest::Response, body: Vec<u8>) -> Self {
+        let headers = resp.headers();
+        Self {
+            url: url.to_string(),
+            status: resp.status().as_u16(),
+            location: header_string(headers, header::LOCATION),
+            content_type: header_string(headers, header::CONTENT_TYPE),
+            etag: header_string(headers, header::ETAG),
+            last_modified: header_string(headers, header::LAST_MODIFIED),
+            fetched_at: now(),
+            body,
+        }
+    }
+
+    pub fn is_success(&self) -> bool {
+        (200..300).contains(&self.status)
+    }
+
+    pub fn is_redirection(&self) -> bool {
+        (300..400).contains(&self.status)
+    }
+
+    pub fn is_fresh(&self) -> bool {
+        now().saturating_sub(self.fetched_at) < FRESH_SECONDS
+    }
+
+    // Takes over the validators of a 304 response
+    pub fn revalidated(mut self, resp: &reqwest::Response) -> Self {
+        let headers = resp.headers();
+        if let Some(etag) = header_string(headers, header::ETAG) {
+            self.etag = Some(etag);
+        }
+        if let Some(last_modified) = header_string(headers, header::LAST_MODIFIED) {
+            self.last_modified = Some(last_modified);
+        }
+        self.fetched_at = now();
+        self
+    }
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or_default()
+}
+
+fn header_string(headers: &HeaderMap, name: header::HeaderName) -> Option<String> {
+    headers
+        .get(name)
+        .and_then(|value| value.to_str().ok())
+        .map(String::from)
+}
+
+fn entry_paths(user_agent: &str, url: &Url) -> Option<(PathBuf, PathBuf)> {
+    let dir = xdg::BaseDirectories::with_prefix("webber.timsueberkrueb")
+        .ok()?
+        .create_cache_directory("http")
+        .ok()?;
+    let key = format!("{:x}", md5::compute(format!("{}\n{}", user_agent, url)));
+    Some((
+        dir.join(format!("{}.json", key)),
+        dir.join(format!("{}.body", key)),
+    ))
+}
+
+pub fn load(user_agent: &str, url: &Url) -> Option<Response> {
+    let (meta_path, body_path) = entry_paths(user_agent, url)?;
+    let meta = fs::read_to_string(meta_path).ok()?;
+    let mut resp: Response = serde_json::from_str(&meta).ok()?;
+    // Guards against hash collisions
+    if resp.url != url.as_str() {
+        return None;
+    }
+    resp.body = fs::read(body_path).ok()?;
+    Some(resp)
+}
+
+// The cache is best effort, callers report a failure but carry on with the request
+pub fn store(user_agent: &str, url: &Url, resp: &Response) -> Result<(), String> {
+    let (meta_path, body_path) =
+        entry_paths(user_agent, url).ok_or_else(|| "No cache directory".to_owned())?;
+    let meta = serde_json::to_string(resp).map_err(|err| err.to_string())?;
+    fs::write(&body_path, &resp.body).map_err(|err| err.to_string())?;
+    fs::write(&meta_path, meta).map_err(|err| err.to_string())?;
+    match meta_path.parent() {
+        Some(dir) => prune(dir).map_err(|err| err.to_string()),
+        None => Ok(()),
+    }
+}
+
+fn prune(dir: &Path) -> io::Result<()> {
+    // Both files of an entry share the key as their stem
+    let mut entries = HashMap::new();
+    for file in fs::read_dir(dir)? {
+        let file = file?;
+        let metadata = file.metadata()?;
+        let path = file.path();
+        let key = match path.file_stem() {
+            Some(key) => key.to_owned(),
+            None => continue,
+        };
+        let (modified, size, paths) = entries
+            .entry(key)
+            .or_insert_with(|| (UNIX_EPOCH, 0, Vec::new()));
+        *modified = metadata.modified()?.max(*modified);
+        *size += metadata.len();
+        paths.push(path);
+    }
+
+    let mut entries = entries.into_values().collect::<Vec<_>>();
+    entries.sort_by_key(|(modified, _, _)| Reverse(*modified));
+    let now = SystemTime::now();
+    let mut total_size = 0;
+    for (modified, size, paths) in entries {
+        total_size += size;
+        let expired = now.duration_since(modified).is_ok_and(|age| age > MAX_AGE);
+        if expired || total_size > MAX_SIZE {
+            for path in paths {
+                fs::remove_file(path)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+pub fn is_cacheable(resp: &reqwest::Response) -> bool {
+    let no_store = resp
+        .headers()
+        .get(header::CACHE_CONTROL)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| value.to_ascii_lowercase().contains("no-store"));
+    !no_store && (resp.status().is_success() || resp.status().is_redirection())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn prune_removes_expired_entries() {
+        let dir = std::env::temp_dir().join(format!("webber-cache-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        for key in &["old", "new"] {
+            fs::write(dir.join(format!("{}.json", key)), b"{}").unwrap();
+            fs::write(dir.join(format!("{}.body", key)), b"body").unwrap();
+        }
+        let old = SystemTime::now() - MAX_AGE - Duration::from_secs(60);
+        for ext in &["json", "body"] {
+            fs::File::options()
+                .write(true)
+                .open(dir.join(format!("old.{}", ext)))
+                .unwrap()
+                .set_modified(old)
+                .unwrap();
+        }
+
+        prune(&dir).unwrap();
+        let mut left = fs::read_dir(&dir)
+            .unwrap()
+            .map(|file| file.unwrap().file_name().into_string().unwrap())
+            .collect::<Vec<_>>();
+        left.sort();
+        assert_eq!(left, vec!["new.body", "new.json"]);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
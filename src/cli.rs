@@ -0,0 +1,264 @@
+use std::fs;
+use std::path::PathBuf;
+
+use crate::click;
+use crate::container::{ContainerOptions, UserAgent};
+use crate::core;
+use crate::imaging::{self, IconMask, IconStyle};
+use crate::library;
+use crate::net::Task;
+use crate::patterns;
+
+const USAGE: &str = r#"Usage: webber create --url URL [OPTIONS]
+
+Create a click shortcut for a website without starting the user interface.
+Everything that is not given on the command line is scraped from the site.
+
+Options:
+    --url URL           Url the shortcut opens, https:// is assumed if the
+                        scheme is left out
+    --name NAME         Name of the shortcut
+    --color COLOR       Splash screen color (e.g. #ffffff)
+    --icon URL          Url or path of the icon
+    --icon-background COLOR
+                        Put the icon on a background, either a color or
+                        "theme" for the splash screen color
+    --icon-padding PERCENT
+                        Space around the icon (0-40)
+    --icon-mask MASK    Shape of the icon, none or squircle
+    --pattern PATTERN   Url pattern that stays inside the app, can be repeated
+                        (default: the host of the url and its subdomains)
+    --version VERSION   Version of the package (default: 1.0.0, or the next
+                        version if the shortcut was built before)
+    --package-name NAME Name the app is installed under (default: derived
+                        from the url)
+    --user-agent UA     User agent the web app uses, either a string or one of
+                        the presets mobile_chrome, mobile_firefox or desktop
+    --container-flag FLAG
+                        Additional webapp-container flag, can be repeated
+                        (e.g. --container-flag=--fullscreen)
+    --no-session-cookies
+                        Don't store session cookies
+    --output PATH       Where to write the click package
+    --no-scrape         Don't load the site, use the given options only
+    -h, --help          Show this help
+"#;
+
+#[derive(Default)]
+struct CreateOptions {
+    url: String,
+    name: Option<String>,
+    color: Option<String>,
+    icon: Option<String>,
+    icon_style: IconStyle,
+    patterns: Vec<String>,
+    version: Option<String>,
+    package_name: Option<String>,
+    container_options: ContainerOptions,
+    output: Option<PathBuf>,
+    no_scrape: bool,
+}
+
+pub fn is_command(args: &[String]) -> bool {
+    matches!(
+        args.first().map(String::as_str),
+        Some("create") | Some("--help") | Some("-h")
+    )
+}
+
+pub fn run(args: &[String]) -> i32 {
+    match args.first().map(String::as_str) {
+        Some("create") => {}
+        _ => {
+            print!("{}", USAGE);
+            return 0;
+        }
+    }
+    if args.iter().any(|arg| arg == "--help" || arg == "-h") {
+        print!("{}", USAGE);
+        return 0;
+    }
+
+    match parse_create_options(&args[1..]).and_then(create) {
+        Ok(path) => {
+            println!("{}", path.display());
+            0
+        }
+        Err(err) => {
+            eprintln!("webber: {}", err);
+            1
+        }
+    }
+}
+
+fn parse_create_options(args: &[String]) -> Result<CreateOptions, String> {
+    let mut options = CreateOptions::default();
+    let mut url = None;
+    let mut args = args.iter();
+
+    while let Some(arg) = args.next() {
+        if arg == "--no-scrape" {
+            options.no_scrape = true;
+            continue;
+        }
+        if arg == "--no-session-cookies" {
+            options.container_options.store_session_cookies = false;
+            continue;
+        }
+        let (key, value) = if let Some(pos) = arg.find('=') {
+            (&arg[..pos], arg[pos + 1..].to_owned())
+        } else {
+            let value = args
+                .next()
+                .ok_or_else(|| format!("Missing value for {}", arg))?;
+            (arg.as_str(), value.clone())
+        };
+        match key {
+            "--url" => url = Some(value),
+            "--name" => options.name = Some(value),
+            "--color" => options.color = Some(value),
+            "--icon" => options.icon = Some(value),
+            "--icon-background" => {
+                options.icon_style.background = true;
+                if value != "theme" {
+                    imaging::parse_color(&value)?;
+                    options.icon_style.background_color = Some(value);
+                }
+            }
+            "--icon-padding" => {
+                options.icon_style.padding = value
+                    .parse()
+                    .map_err(|_| format!("Invalid padding: {}", value))?
+            }
+            "--icon-mask" => match value.as_str() {
+                "none" | "squircle" => options.icon_style.mask = IconMask::from_name(&value),
+                _ => return Err(format!("Unknown icon mask: {}", value)),
+            },
+            "--pattern" => options.patterns.push(value),
+            "--version" => options.version = Some(value),
+            "--package-name" => options.package_name = Some(value),
+            "--user-agent" => options.container_options.user_agent = UserAgent::parse(&value),
+            "--container-flag" => {
+                if !options.container_options.parse_arg(&value) {
+                    return Err(format!("Unknown webapp-container flag: {}", value));
+                }
+            }
+            "--output" => options.output = Some(PathBuf::from(value)),
+            _ => return Err(format!("Unknown option: {}\n\n{}", key, USAGE)),
+        }
+    }
+
+    options.url = url.ok_or_else(|| format!("Missing --url\n\n{}", USAGE))?;
+    Ok(options)
+}
+
+fn create(options: CreateOptions) -> Result<PathBuf, String> {
+    let url =
+        core::validate_url(options.url.clone()).map_err(|err| format!("Invalid url: {}", err))?;
+
+    let scraped = if options.no_scrape {
+        None
+    } else {
+        let user_agent = &options.container_options.user_agent;
+        let http_fallback = !core::has_scheme(&options.url);
+        let res = core::scrape_url(url.clone(), user_agent, &Task::detached(), http_fallback)
+            .map_err(|err| format!("Failed to load site: {}", err))?;
+        for warning in &res.warnings {
+            eprintln!("webber: warning: {}", warning);
+        }
+        Some(res)
+    };
+    let url = match scraped {
+        Some(ref res) if !res.fallback_url.is_empty() => {
+            eprintln!(
+                "webber: warning: secure connections to {} failed, using {}",
+                url, res.fallback_url
+            );
+            core::validate_url(res.fallback_url.clone())?
+        }
+        _ => url,
+    };
+
+    let name = options
+        .name
+        .or_else(|| {
+            let res = scraped.as_ref()?;
+            vec![&res.short_name, &res.site_name, &res.title]
+                .into_iter()
+                .find(|name| !name.is_empty())
+                .cloned()
+        })
+        .ok_or_else(|| "No name given and none could be scraped, use --name".to_owned())?;
+    let theme_color = options
+        .color
+        .or_else(|| scraped.as_ref().map(|res| res.theme_color.clone()))
+        .filter(|color| !color.is_empty())
+        .unwrap_or_else(|| "#ffffff".to_owned());
+    let custom_icon = options.icon.is_some();
+    let icon_url = options
+        .icon
+        .or_else(|| scraped.as_ref().map(|res| res.icon_url.clone()))
+        .unwrap_or_default();
+    let url_patterns = if !options.patterns.is_empty() {
+        options
+            .patterns
+            .iter()
+            .map(|pattern| patterns::normalize(pattern))
+            .collect()
+    } else {
+        scraped
+            .as_ref()
+            .map(|res| res.default_url_patterns.clone())
+            .unwrap_or_else(|| core::default_url_patterns(&url))
+    };
+
+    let mut package = click::Package {
+        url: url.to_string(),
+        name,
+        icon_url,
+        theme_color,
+        url_patterns: url_patterns.join(","),
+        container_options: options.container_options,
+        icon_style: options.icon_style,
+        package_name: options.package_name,
+        custom_icon,
+        ..Default::default()
+    };
+    library::resolve_appname(&mut package);
+    // Building anyway would replace the other shortcut once installed, like in the user interface
+    if let Some(other) = library::clash(&package) {
+        return Err(format!(
+            "The package name {} is already used by the shortcut for {}, pick another one with --package-name",
+            package.appname(),
+            other
+        ));
+    }
+    // Like in the user interface, a rebuild needs a higher version to be installable
+    package.version = match options.version {
+        Some(version) => version,
+        None => library::next_version(&package.appname())?,
+    };
+
+    let output = match options.output {
+        Some(ref path) if path.is_dir() => path.join(package.click_filename()),
+        Some(path) => path,
+        None => PathBuf::from(package.click_filename()),
+    };
+
+    let mut warnings = Vec::new();
+    let click_path = click::create_package(package.clone(), &Task::detached(), &mut warnings)
+        .map_err(|err| err.to_string())?;
+    for warning in warnings {
+        eprintln!("webber: warning: {}", warning);
+    }
+    fs::copy(&click_path, &output)
+        .map_err(|err| format!("Failed to write {}: {}", output.display(), err))?;
+    if let Err(err) = library::add(package) {
+        eprintln!(
+            "webber: warning: failed to save shortcut to library: {}",
+            err
+        );
+    }
+
+    Ok(output)
+}
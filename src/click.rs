@@ -1,44 +1,225 @@
-use std::error::Error;
+use std::fmt;
 use std::fs;
 use std::io::{self, Write};
-use std::path::Path;
-use std::process::Command;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::archive;
+use crate::container::{self, ContainerOptions};
+use crate::imaging::{self, IconData, IconStyle};
+use crate::library;
+use crate::net::{self, Task};
+use crate::patterns;
 
 #[derive(Debug)]
+pub enum Error {
+    CacheDir(xdg::BaseDirectoriesError),
+    IconDownload { url: String, reason: String },
+    InvalidIcon { url: String, reason: String },
+    InvalidUrlPattern { pattern: String, reason: String },
+    InvalidVersion { version: String, reason: String },
+    Io(io::Error),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::CacheDir(err) => write!(f, "Cache directory not available: {}", err),
+            Error::IconDownload { url, reason } => {
+                write!(f, "Failed to download icon {}: {}", url, reason)
+            }
+            Error::InvalidIcon { url, reason } => write!(f, "Invalid icon {}: {}", url, reason),
+            Error::InvalidUrlPattern { pattern, reason } => {
+                write!(f, "Invalid url pattern {}: {}", pattern, reason)
+            }
+            Error::InvalidVersion { version, reason } => {
+                write!(f, "Invalid version {}: {}", version, reason)
+            }
+            Error::Io(err) => write!(f, "Failed to write package: {}", err),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<io::Error> for Error {
+    fn from(err: io::Error) -> Self {
+        Error::Io(err)
+    }
+}
+
+impl From<xdg::BaseDirectoriesError> for Error {
+    fn from(err: xdg::BaseDirectoriesError) -> Self {
+        Error::CacheDir(err)
+    }
+}
+
+pub const INITIAL_VERSION: &str = "1.0.0";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct Package {
     pub url: String,
     pub name: String,
     pub theme_color: String,
     pub icon_url: String,
     pub url_patterns: String,
+    pub version: String,
+    pub container_options: ContainerOptions,
+    pub icon_style: IconStyle,
+    // Overrides the name derived from the url
+    pub package_name: Option<String>,
+    // Picked by the user rather than scraped, failing to load it fails the build
+    pub custom_icon: bool,
+}
+
+impl Default for Package {
+    fn default() -> Self {
+        Self {
+            url: String::default(),
+            name: String::default(),
+            theme_color: String::default(),
+            icon_url: String::default(),
+            url_patterns: String::default(),
+            version: INITIAL_VERSION.to_owned(),
+            container_options: ContainerOptions::default(),
+            icon_style: IconStyle::default(),
+            package_name: None,
+            custom_icon: false,
+        }
+    }
 }
 
 impl Package {
-    fn appname(&self) -> String {
-        let url_part = url::Url::parse(&self.url)
-            .ok()
-            .map(|url| url.host_str().map(String::from))
-            .map(|url| url.unwrap_or_else(|| self.url.clone()))
-            .unwrap_or_else(|| self.url.clone());
-        // Remove forbidden characters
-        let ascii = url_part.to_ascii_lowercase();
-        let allowed_chars = ascii
-            .chars()
-            .filter_map(|c| {
-                if c == '.' || c == '_' {
-                    Some('-')
-                } else if ('a'..'z').contains(&c) || c.is_digit(10) {
-                    Some(c)
-                } else {
-                    None
+    pub fn appname(&self) -> String {
+        match self
+            .package_name
+            .as_ref()
+            .map(|name| sanitize_appname(name))
+        {
+            Some(ref name) if !name.is_empty() => name.clone(),
+            _ => self.derived_appname(false),
+        }
+    }
+
+    // Name derived from the url, `with_path` tells apart shortcuts to different parts of a site
+    pub fn derived_appname(&self, with_path: bool) -> String {
+        let url_part = match url::Url::parse(&self.url) {
+            Ok(url) => {
+                let mut part = url.host_str().unwrap_or(&self.url).to_owned();
+                if let Some(port) = url.port() {
+                    part.push_str(&format!("-{}", port));
+                }
+                if with_path {
+                    part.push_str(url.path());
                 }
-            })
-            .collect::<String>();
-        format!("webapp-{}", allowed_chars)
+                part
+            }
+            Err(_) => self.url.clone(),
+        };
+        format!("webapp-{}", sanitize_appname(&url_part))
+    }
+
+    // Whether both packages open the same page, in which case they are the same app
+    pub fn same_target(&self, other: &Package) -> bool {
+        let key = |url: &str| {
+            url::Url::parse(url)
+                .map(|url| {
+                    format!(
+                        "{}:{}{}",
+                        url.host_str().unwrap_or_default(),
+                        url.port_or_known_default().unwrap_or_default(),
+                        url.path().trim_end_matches('/')
+                    )
+                })
+                .unwrap_or_else(|_| url.to_owned())
+        };
+        key(&self.url) == key(&other.url)
+    }
+
+    pub fn click_filename(&self) -> String {
+        format!("{}.webber_{}_all.click", self.appname(), self.version)
+    }
+}
+
+// Keeps only the characters allowed in click package names
+pub fn sanitize_appname(name: &str) -> String {
+    name.to_ascii_lowercase()
+        .chars()
+        .filter_map(|c| match c {
+            '.' | '_' | '/' | ':' | ' ' => Some('-'),
+            'a'..='z' | '0'..='9' | '-' => Some(c),
+            _ => None,
+        })
+        .collect::<String>()
+        .trim_matches('-')
+        .to_owned()
+}
+
+// Increments the last numeric component, e.g. 1.0.0 -> 1.0.1
+pub fn bump_version(version: &str) -> String {
+    let mut parts = version.split('.').map(String::from).collect::<Vec<_>>();
+    match parts.last().and_then(|last| last.parse::<u64>().ok()) {
+        Some(last) => {
+            let len = parts.len();
+            parts[len - 1] = (last + 1).to_string();
+            parts.join(".")
+        }
+        None if version.is_empty() => INITIAL_VERSION.to_owned(),
+        None => format!("{}.1", version),
+    }
+}
+
+// Checks the Debian version syntax click uses: [epoch:]upstream[-revision]
+pub fn validate_version(version: &str) -> Result<(), String> {
+    let (epoch, rest) = match version.find(':') {
+        Some(pos) => (Some(&version[..pos]), &version[pos + 1..]),
+        None => (None, version),
+    };
+    if let Some(epoch) = epoch {
+        if epoch.is_empty() || !epoch.chars().all(|c| c.is_ascii_digit()) {
+            return Err("The epoch must be a number".to_owned());
+        }
+    }
+    let (upstream, revision) = match rest.rfind('-') {
+        Some(pos) => (&rest[..pos], Some(&rest[pos + 1..])),
+        None => (rest, None),
+    };
+    if !upstream.starts_with(|c: char| c.is_ascii_digit()) {
+        return Err("The version must start with a digit".to_owned());
+    }
+    let upstream_char = |c: char| c.is_ascii_alphanumeric() || ".+~-".contains(c);
+    if !upstream.chars().all(upstream_char) {
+        return Err("Only letters, digits and . + ~ - are allowed".to_owned());
     }
+    if let Some(revision) = revision {
+        let revision_char = |c: char| c.is_ascii_alphanumeric() || ".+~".contains(c);
+        if revision.is_empty() || !revision.chars().all(revision_char) {
+            return Err("Only letters, digits and . + ~ are allowed after the last -".to_owned());
+        }
+    }
+    Ok(())
 }
 
-pub fn create_package(package: Package) -> Result<(), Box<dyn std::error::Error>> {
+// Problems that don't make the package unusable, e.g. an icon style that had to be left
+// out, are added to `warnings`
+pub fn create_package(
+    package: Package,
+    task: &Task,
+    warnings: &mut Vec<String>,
+) -> Result<PathBuf, Error> {
+    validate_version(&package.version).map_err(|reason| Error::InvalidVersion {
+        version: package.version.clone(),
+        reason,
+    })?;
+    for pattern in package.url_patterns.split(',').filter(|p| !p.is_empty()) {
+        patterns::validate(pattern).map_err(|reason| Error::InvalidUrlPattern {
+            pattern: pattern.to_owned(),
+            reason,
+        })?;
+    }
+
     let path = xdg::BaseDirectories::new()?
         .get_cache_home()
         .join("webber.timsueberkrueb/click-build");
@@ -60,36 +241,18 @@ pub fn create_package(package: Package) -> Result<(), Box<dyn std::error::Error>
     write_file(&debian_binary, "2.0\n")?;
     write_file(
         &control.join(Path::new("control")),
-        &control_control_content(&package.appname()),
-    )?;
-    write_file(
-        &control.join(Path::new("manifest")),
-        &control_manifest_content(&package.appname(), &package.name),
+        &control_control_content(&package.appname(), &package.version),
     )?;
     write_file(&data.join(Path::new("preinst")), control_preinst_content())?;
 
-    // TODO: md5sums
     write_file(
         &data.join(Path::new("shortcut.apparmor")),
         data_apparmor_content(),
     )?;
 
-    let ext = url::Url::parse(&package.icon_url)
-        .ok()
-        .map(|icon| Some(icon.path_segments()?.map(String::from).collect::<Vec<_>>()))
-        .map(|segments| segments?.iter().rev().cloned().next())
-        .map(|last| last?.rsplit('.').map(String::from).next())
-        .unwrap_or_default();
-
-    let icon_filename = if let Some(ext) = ext {
-        let icon_fname = format!("icon.{}", ext);
-        download_file(package.icon_url, &data.join(Path::new(&icon_fname)))?;
-        icon_fname
-    } else {
-        let icon_fname = "icon.svg".to_owned();
-        write_icon(&data.join(Path::new(&icon_fname)))?;
-        icon_fname
-    };
+    let icon = render_icon(&package, task, warnings)?;
+    let icon_filename = format!("icon.{}", icon.extension());
+    fs::write(data.join(Path::new(&icon_filename)), icon.bytes())?;
 
     write_file(
         &data.join(Path::new("shortcut.desktop")),
@@ -97,11 +260,28 @@ pub fn create_package(package: Package) -> Result<(), Box<dyn std::error::Error>
             &package.name,
             &package.url,
             &package.url_patterns,
+            &package.container_options,
             &icon_filename,
             &package.theme_color,
         ),
     )?;
 
+    // The control files describe the data, so they have to be written last
+    let data_files = list_files(&data)?;
+    write_file(
+        &control.join(Path::new("md5sums")),
+        &control_md5sums_content(&data, &data_files)?,
+    )?;
+    write_file(
+        &control.join(Path::new("manifest")),
+        &control_manifest_content(
+            &package.appname(),
+            &package.name,
+            &package.version,
+            installed_size(&data, &data_files)?,
+        ),
+    )?;
+
     let control_tar_gz = path.join(Path::new("control.tar.gz"));
     let data_tar_gz = path.join(Path::new("data.tar.gz"));
 
@@ -120,14 +300,117 @@ pub fn create_package(package: Package) -> Result<(), Box<dyn std::error::Error>
         ],
     )?;
 
-    Ok(())
+    Ok(click_path)
 }
 
-fn download_file(url: String, target: &Path) -> Result<(), Box<dyn Error>> {
-    let mut resp = reqwest::get(&url)?;
-    let mut file = fs::File::create(target)?;
-    io::copy(&mut resp, &mut file)?;
-    Ok(())
+// The icon as it will be embedded in the package
+pub fn render_icon(
+    package: &Package,
+    task: &Task,
+    warnings: &mut Vec<String>,
+) -> Result<IconData, Error> {
+    let icon = if package.icon_url.is_empty() {
+        fallback_icon(package)
+    } else {
+        match load_icon(package, task, warnings) {
+            Ok(icon) => icon,
+            // A scraped icon that turns out to be a 404 or an error page is as good as none
+            Err(err) if !package.custom_icon => {
+                warnings.push(format!("Using a letter avatar instead: {}", err));
+                fallback_icon(package)
+            }
+            Err(err) => return Err(err),
+        }
+    };
+    Ok(imaging::compose(
+        icon,
+        &package.icon_style,
+        &package.theme_color,
+        warnings,
+    ))
+}
+
+fn load_icon(
+    package: &Package,
+    task: &Task,
+    warnings: &mut Vec<String>,
+) -> Result<IconData, Error> {
+    let user_agent = package.container_options.user_agent.effective_string();
+    let (bytes, content_type) = download_file(
+        &package.icon_url,
+        package.custom_icon,
+        user_agent,
+        task,
+        warnings,
+    )?;
+    imaging::process_icon(&bytes, content_type.as_deref()).map_err(|reason| Error::InvalidIcon {
+        url: package.icon_url.clone(),
+        reason,
+    })
+}
+
+// Sites without an icon get their initials, so that they can be told apart on the launcher
+fn fallback_icon(package: &Package) -> IconData {
+    let host = url::Url::parse(&package.url)
+        .ok()
+        .and_then(|url| url.host_str().map(String::from))
+        .unwrap_or_default();
+    let host = host.trim_start_matches("www.");
+    IconData::Svg(imaging::letter_avatar(
+        &package.name,
+        host,
+        &package.theme_color,
+    ))
+}
+
+// Returns the content and, for remote files, the content type. Local files are only read if
+// the user picked them or they are one of our stored icons, scraped urls could point anywhere.
+fn download_file(
+    url: &str,
+    allow_local: bool,
+    user_agent: &str,
+    task: &Task,
+    warnings: &mut Vec<String>,
+) -> Result<(Vec<u8>, Option<String>), Error> {
+    let download_error = |reason: String| Error::IconDownload {
+        url: url.to_owned(),
+        reason,
+    };
+    // Anything that is not an absolute url is taken as a path
+    let local_path = match url::Url::parse(url) {
+        Ok(parsed) if parsed.scheme() == "file" => Some(
+            parsed
+                .to_file_path()
+                .map_err(|_| download_error("Invalid file url".to_owned()))?,
+        ),
+        Ok(_) => None,
+        Err(_) => Some(PathBuf::from(url)),
+    };
+    if let Some(path) = local_path {
+        if !allow_local && !is_stored_icon(&path) {
+            return Err(download_error("Not a picked or stored icon".to_owned()));
+        }
+        let bytes = fs::read(path).map_err(|err| download_error(err.to_string()))?;
+        return Ok((bytes, None));
+    }
+    let url = url::Url::parse(url).map_err(|err| download_error(err.to_string()))?;
+    let client = net::client(Some(user_agent)).map_err(|err| download_error(err.to_string()))?;
+    let fetched = net::fetch(&client, &url, task);
+    warnings.append(&mut client.take_warnings());
+    let (resp, _) = fetched.map_err(|err| download_error(err.to_string()))?;
+    net::check_status(&resp).map_err(|err| download_error(err.to_string()))?;
+    Ok((resp.body, resp.content_type))
+}
+
+fn is_stored_icon(path: &Path) -> bool {
+    // Resolves links and .., the file has to really be inside the directory
+    match (
+        library::icons_dir().map(fs::canonicalize),
+        fs::canonicalize(path),
+    ) {
+        (Ok(Ok(dir)), Ok(path)) => path.starts_with(dir),
+        _ => false,
+    }
 }
 
 fn create_ar(filepath: &Path, files: &[(&Path, &str)]) -> io::Result<()> {
@@ -141,30 +424,34 @@ fn create_ar(filepath: &Path, files: &[(&Path, &str)]) -> io::Result<()> {
 }
 
 fn create_tar_gz(filepath: &Path, dir: &Path) -> io::Result<()> {
-    // FIXME: We cannot use the `tar` crate as for some reason the filepaths
-    // need to start with ./ and this seem to get normalized away in Rust paths.
-    // This workaround should be okay because we control the filepath, but it is ugly
-    // nevertheless.
-    Command::new(
-        std::env::current_exe()
-            .unwrap()
-            .parent()
-            .unwrap()
-            .join("tar"),
-    )
-    .args(&[
-        "--transform",
-        &format!(
-            "flags=r;s|{}|.|",
-            dir.file_name().unwrap().to_str().unwrap()
-        ),
-        "-czf",
-        filepath.to_str().unwrap(),
-        dir.file_name().unwrap().to_str().unwrap(),
-    ])
-    .current_dir(&dir.join(".."))
-    .output()?;
-    Ok(())
+    archive::write_tar_gz(filepath, dir)
+}
+
+// Lists all files below `dir` relative to it, in a stable order
+fn list_files(dir: &Path) -> io::Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    let mut entries = fs::read_dir(dir)?.collect::<io::Result<Vec<_>>>()?;
+    entries.sort_by_key(|entry| entry.file_name());
+    for entry in entries {
+        let name = PathBuf::from(entry.file_name());
+        if entry.file_type()?.is_dir() {
+            for file in list_files(&entry.path())? {
+                files.push(name.join(file));
+            }
+        } else {
+            files.push(name);
+        }
+    }
+    Ok(files)
+}
+
+// Size in KiB, like `du --apparent-size -k` which click uses
+fn installed_size(dir: &Path, files: &[PathBuf]) -> io::Result<u64> {
+    let mut bytes = 0;
+    for file in files {
+        bytes += fs::metadata(dir.join(file))?.len();
+    }
+    Ok(bytes.div_ceil(1024))
 }
 
 fn mkdir(dirname: &Path) -> io::Result<()> {
@@ -177,39 +464,57 @@ fn write_file(filename: &Path, content: &str) -> io::Result<()> {
     Ok(())
 }
 
-fn control_control_content(appname: &str) -> String {
+fn control_control_content(appname: &str, version: &str) -> String {
     format!(
         r#"Package: {}.webber
-Version: 1.0.0
+Version: {}
 Click-Version: 0.4
 Architecture: all
 Maintainer: Webber <noreply@ubports.com>
 Description: Shortcut
 "#,
-        appname,
+        appname, version,
     )
 }
 
-fn control_manifest_content(appname: &str, title: &str) -> String {
-    format!(
-        r#"{{
-    "architecture": "all",
-    "description": "Shortcut",
-    "framework": "ubuntu-sdk-16.04",
-    "hooks": {{
-        "{}": {{
+fn control_md5sums_content(dir: &Path, files: &[PathBuf]) -> io::Result<String> {
+    let mut content = String::new();
+    for file in files {
+        let digest = md5::compute(fs::read(dir.join(file))?);
+        content.push_str(&format!("{:x}  {}\n", digest, file.display()));
+    }
+    Ok(content)
+}
+
+fn control_manifest_content(
+    appname: &str,
+    title: &str,
+    version: &str,
+    installed_size: u64,
+) -> String {
+    let mut hooks = serde_json::Map::new();
+    hooks.insert(
+        appname.to_owned(),
+        serde_json::json!({
             "apparmor": "shortcut.apparmor",
             "desktop": "shortcut.desktop"
-        }}
-    }},
-    "installed-size": "30",
-    "maintainer": "Webber <noreply@ubports.com>",
-    "name": "{}.webber",
-    "title": "{}",
-    "version": "1.0.0"
-}}
-"#,
-        appname, appname, title,
+        }),
+    );
+    let manifest = serde_json::json!({
+        "architecture": "all",
+        "description": "Shortcut",
+        "framework": "ubuntu-sdk-16.04",
+        "hooks": hooks,
+        "installed-size": installed_size.to_string(),
+        "maintainer": "Webber <noreply@ubports.com>",
+        "name": format!("{}.webber", appname),
+        "title": title,
+        "version": version
+    });
+    // Serializing a json value can't fail
+    format!(
+        "{}\n",
+        serde_json::to_string_pretty(&manifest).unwrap_or_default()
     )
 }
 
@@ -236,26 +541,125 @@ fn data_desktop_content(
     title: &str,
     url: &str,
     url_patterns: &str,
+    options: &ContainerOptions,
     icon_fname: &str,
     theme_color: &str,
 ) -> String {
+    let mut args = vec![format!("--webappUrlPatterns={}", url_patterns)];
+    args.extend(options.to_args());
+    args.push(url.to_owned());
+    let args = args
+        .iter()
+        .map(|arg| container::quote_exec_arg(arg))
+        .collect::<Vec<_>>()
+        .join(" ");
     format!(
         r#"[Desktop Entry]
 Name={}
-Exec=webapp-container --webappUrlPatterns={} --store-session-cookies {}
+Exec=webapp-container {}
 Icon={}
 Terminal=false
 Type=Application
 X-Ubuntu-Touch=true
 X-Ubuntu-Splash-Color={}
 "#,
-        title, url_patterns, url, icon_fname, theme_color
+        title, args, icon_fname, theme_color
     )
 }
 
-fn write_icon(path: &Path) -> io::Result<()> {
-    let bytes = include_bytes!("../assets/logo.svg");
-    let mut file = fs::File::create(path)?;
-    file.write_all(bytes)?;
-    Ok(())
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bump_version_increments_the_last_number() {
+        assert_eq!(bump_version("1.0.0"), "1.0.1");
+        assert_eq!(bump_version("1.0.9"), "1.0.10");
+        assert_eq!(bump_version("2"), "3");
+        assert_eq!(bump_version("1.0.0~beta"), "1.0.0~beta.1");
+        assert_eq!(bump_version(""), INITIAL_VERSION);
+    }
+
+    #[test]
+    fn sanitize_appname_keeps_allowed_characters() {
+        assert_eq!(sanitize_appname("Zoom.us"), "zoom-us");
+        assert_eq!(sanitize_appname("xyz/path_to:8080"), "xyz-path-to-8080");
+        assert_eq!(sanitize_appname("  Café Zürich! "), "caf-zrich");
+        assert_eq!(sanitize_appname("-.-"), "");
+    }
+
+    #[test]
+    fn appname_prefers_the_sanitized_override() {
+        let package = Package {
+            url: "https://www.example.com:8080/app".to_owned(),
+            ..Default::default()
+        };
+        assert_eq!(package.appname(), "webapp-www-example-com-8080");
+        assert_eq!(
+            package.derived_appname(true),
+            "webapp-www-example-com-8080-app"
+        );
+        let package = Package {
+            package_name: Some("My App".to_owned()),
+            ..package
+        };
+        assert_eq!(package.appname(), "my-app");
+    }
+
+    #[test]
+    fn validate_version_follows_debian_syntax() {
+        for version in &["1.0.0", "1:2.0", "1.0.0-1", "1.0~rc1+git", "2.0-beta-1"] {
+            assert!(validate_version(version).is_ok(), "{}", version);
+        }
+        for version in &[
+            "", "v1.0", "1.0 beta", "1.0-", "a:1.0", "1.0\"\n", "1.0-b_1",
+        ] {
+            assert!(validate_version(version).is_err(), "{}", version);
+        }
+    }
+
+    #[test]
+    fn scraped_icons_are_not_read_from_disk() {
+        let path = std::env::temp_dir().join(format!("webber-icon-{}.png", std::process::id()));
+        fs::write(&path, b"icon").unwrap();
+        let url = url::Url::from_file_path(&path).unwrap().to_string();
+        for icon in &[url.as_str(), path.to_str().unwrap()] {
+            let mut warnings = Vec::new();
+            assert!(download_file(icon, false, "", &Task::detached(), &mut warnings).is_err());
+            let (bytes, _) =
+                download_file(icon, true, "", &Task::detached(), &mut warnings).unwrap();
+            assert_eq!(bytes, b"icon");
+        }
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn unusable_scraped_icons_become_a_letter_avatar() {
+        let mut package = Package {
+            url: "https://example.com".to_owned(),
+            name: "Example".to_owned(),
+            icon_url: "/does/not/exist.png".to_owned(),
+            ..Default::default()
+        };
+        let mut warnings = Vec::new();
+        let icon = render_icon(&package, &Task::detached(), &mut warnings).unwrap();
+        assert_eq!(icon.extension(), "svg");
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].starts_with("Using a letter avatar instead"));
+
+        // A picked icon that can't be read is an error the user has to see
+        package.custom_icon = true;
+        assert!(render_icon(&package, &Task::detached(), &mut Vec::new()).is_err());
+    }
+
+    #[test]
+    fn manifest_escapes_the_title() {
+        let manifest =
+            control_manifest_content("webapp-example-com", "Say \"hi\" \\o/", "1.0.0", 4);
+        let manifest: serde_json::Value = serde_json::from_str(&manifest).unwrap();
+        assert_eq!(manifest["title"], "Say \"hi\" \\o/");
+        assert_eq!(manifest["name"], "webapp-example-com.webber");
+        assert_eq!(manifest["installed-size"], "4");
+        assert!(manifest["hooks"]["webapp-example-com"].is_object());
+    }
 }
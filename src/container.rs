@@ -0,0 +1,196 @@
+use serde::{Deserialize, Serialize};
+
+// What the Ubuntu Touch webview identifies as when no user agent is overridden
+pub const WEBVIEW_USER_AGENT: &str = "Mozilla/5.0 (Linux; Ubuntu 16.04 like Android 9) AppleWebKit/537.36 Chrome/77.0.3865.129 Mobile Safari/537.36";
+const MOBILE_CHROME_USER_AGENT: &str = "Mozilla/5.0 (Linux; Android 9; Pixel 3) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/78.0.3904.108 Mobile Safari/537.36";
+const MOBILE_FIREFOX_USER_AGENT: &str =
+    "Mozilla/5.0 (Android 9; Mobile; rv:68.0) Gecko/68.0 Firefox/68.0";
+const DESKTOP_USER_AGENT: &str = "Mozilla/5.0 (X11; Linux x86_64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/78.0.3904.108 Safari/537.36";
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UserAgent {
+    #[default]
+    Default,
+    MobileChrome,
+    MobileFirefox,
+    Desktop,
+    Custom(String),
+}
+
+impl UserAgent {
+    pub fn from_preset(preset: &str, custom: &str) -> Self {
+        match preset {
+            "mobile_chrome" => UserAgent::MobileChrome,
+            "mobile_firefox" => UserAgent::MobileFirefox,
+            "desktop" => UserAgent::Desktop,
+            "custom" if !custom.trim().is_empty() => UserAgent::Custom(custom.trim().to_owned()),
+            _ => UserAgent::Default,
+        }
+    }
+
+    // Accepts either a preset name or a literal user agent string
+    pub fn parse(s: &str) -> Self {
+        let s = s.trim();
+        match UserAgent::from_preset(s, "") {
+            UserAgent::Default if s != "default" && !s.is_empty() => UserAgent::from_string(s),
+            preset => preset,
+        }
+    }
+
+    fn from_string(s: &str) -> Self {
+        [
+            UserAgent::MobileChrome,
+            UserAgent::MobileFirefox,
+            UserAgent::Desktop,
+        ]
+        .iter()
+        .find(|preset| preset.override_string() == Some(s))
+        .cloned()
+        .unwrap_or_else(|| UserAgent::Custom(s.to_owned()))
+    }
+
+    // The string passed to webapp-container, `None` keeps the webview default
+    pub fn override_string(&self) -> Option<&str> {
+        match self {
+            UserAgent::Default => None,
+            UserAgent::MobileChrome => Some(MOBILE_CHROME_USER_AGENT),
+            UserAgent::MobileFirefox => Some(MOBILE_FIREFOX_USER_AGENT),
+            UserAgent::Desktop => Some(DESKTOP_USER_AGENT),
+            UserAgent::Custom(s) => Some(s.as_str()),
+        }
+    }
+
+    // The string the installed app will actually send
+    pub fn effective_string(&self) -> &str {
+        self.override_string().unwrap_or(WEBVIEW_USER_AGENT)
+    }
+}
+
+// Flags passed to webapp-container in the generated desktop file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ContainerOptions {
+    pub store_session_cookies: bool,
+    pub fullscreen: bool,
+    pub enable_back_forward: bool,
+    pub enable_addressbar: bool,
+    pub local_content_can_access_remote_urls: bool,
+    pub open_external_url_in_overlay: bool,
+    pub enable_media_hub_audio: bool,
+    pub user_agent: UserAgent,
+    pub webapp_model_search_path: Option<String>,
+}
+
+impl Default for ContainerOptions {
+    fn default() -> Self {
+        Self {
+            store_session_cookies: true,
+            fullscreen: false,
+            enable_back_forward: false,
+            enable_addressbar: false,
+            local_content_can_access_remote_urls: false,
+            open_external_url_in_overlay: false,
+            enable_media_hub_audio: false,
+            user_agent: UserAgent::Default,
+            webapp_model_search_path: None,
+        }
+    }
+}
+
+impl ContainerOptions {
+    pub fn to_args(&self) -> Vec<String> {
+        let flags = [
+            ("--store-session-cookies", self.store_session_cookies),
+            ("--fullscreen", self.fullscreen),
+            ("--enable-back-forward", self.enable_back_forward),
+            ("--enable-addressbar", self.enable_addressbar),
+            (
+                "--local-content-can-access-remote-urls",
+                self.local_content_can_access_remote_urls,
+            ),
+            (
+                "--open-external-url-in-overlay",
+                self.open_external_url_in_overlay,
+            ),
+            ("--enable-media-hub-audio", self.enable_media_hub_audio),
+        ];
+        let mut args = flags
+            .iter()
+            .filter(|(_, enabled)| *enabled)
+            .map(|(flag, _)| flag.to_string())
+            .collect::<Vec<_>>();
+        if let Some(user_agent) = self.user_agent.override_string() {
+            args.push(format!("--user-agent-string={}", user_agent));
+        }
+        if let Some(path) = non_empty(&self.webapp_model_search_path) {
+            args.push(format!("--webappModelSearchPath={}", path));
+        }
+        args
+    }
+
+    // Applies a single webapp-container argument, returns false if it is not known
+    pub fn parse_arg(&mut self, arg: &str) -> bool {
+        if let Some(value) = arg.strip_prefix("--user-agent-string=") {
+            self.user_agent = UserAgent::from_string(value);
+            return true;
+        }
+        if let Some(value) = arg.strip_prefix("--webappModelSearchPath=") {
+            self.webapp_model_search_path = Some(value.to_owned());
+            return true;
+        }
+        match arg {
+            "--store-session-cookies" => self.store_session_cookies = true,
+            "--fullscreen" => self.fullscreen = true,
+            "--enable-back-forward" => self.enable_back_forward = true,
+            "--enable-addressbar" => self.enable_addressbar = true,
+            "--local-content-can-access-remote-urls" => {
+                self.local_content_can_access_remote_urls = true
+            }
+            "--open-external-url-in-overlay" => self.open_external_url_in_overlay = true,
+            "--enable-media-hub-audio" => self.enable_media_hub_audio = true,
+            _ => return false,
+        }
+        true
+    }
+}
+
+fn non_empty(value: &Option<String>) -> Option<&str> {
+    value.as_ref().map(|s| s.trim()).filter(|s| !s.is_empty())
+}
+
+// Quotes an argument for the Exec key of a desktop entry if it contains any of the
+// reserved characters of the desktop entry spec. Literal percent signs are doubled,
+// quoted or not, as they would start a field code otherwise. The Exec value is a string
+// as well, so the result gets the string escapes on top, a quoted `$` ends up as `\\$`.
+pub fn quote_exec_arg(arg: &str) -> String {
+    let reserved = |c: char| c.is_whitespace() || "\"'\\><~|&;$*?#()`".contains(c);
+    let arg = arg.replace('%', "%%");
+    if !arg.contains(reserved) {
+        return arg;
+    }
+    let mut quoted = String::from("\"");
+    for c in arg.chars() {
+        if c == '"' || c == '`' || c == '$' || c == '\\' {
+            quoted.push('\\');
+        }
+        quoted.push(c);
+    }
+    quoted.push('"');
+    escape_string(&quoted)
+}
+
+// Escapes of the string value type, values have to fit on a single line
+fn escape_string(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\t' => escaped.push_str("\\t"),
+            '\r' => escaped.push_str("\\r"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
@@ -1,88 +1,661 @@
 use std::string::ToString;
 use url::Url;
 
+use crate::cache;
+use crate::container::UserAgent;
+use crate::icons::{self, IconCandidate, IconSource};
+use crate::net::{self, Error, Task};
+use crate::patterns;
+use crate::webmanifest::{ManifestIcon, WebManifest};
+
+// Query parameters that only tell the site where a visitor came from
+const TRACKING_PARAMS: &[&str] = &[
+    "dclid", "fbclid", "gclid", "igshid", "mc_cid", "mc_eid", "msclkid", "yclid", "_ga",
+];
+
+// Accepts what users type into an address bar, e.g. "  github.com ", and assumes https.
+// Unicode hosts become punycode on parsing.
 pub fn validate_url(url: String) -> Result<Url, String> {
-    let url = Url::parse(&url).map_err(|err| err.to_string())?;
+    let input = url.trim();
+    if input.is_empty() {
+        return Err("The url is empty".to_owned());
+    }
+    let mut url = if has_scheme(input) {
+        Url::parse(input)
+    } else {
+        Url::parse(&format!("https://{}", input))
+    }
+    .map_err(|err| err.to_string())?;
+    if url.scheme() != "https" && url.scheme() != "http" {
+        return Err(format!("Unsupported scheme {}", url.scheme()));
+    }
+    strip_tracking_params(&mut url);
     Ok(url)
 }
 
-pub fn scrape_url(url: Url) -> Result<ScrapeResult, String> {
-    let body = reqwest::get(url.as_ref())
-        .map_err(|err| err.to_string())?
-        .text()
-        .map_err(|err| err.to_string())?;
+// Without an explicit scheme, `scrape_url` may fall back to http
+pub fn has_scheme(input: &str) -> bool {
+    let input = input.trim();
+    match input.find("://") {
+        Some(pos) => {
+            pos > 0
+                && input[..pos]
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '+' || c == '-' || c == '.')
+        }
+        None => false,
+    }
+}
+
+fn strip_tracking_params(url: &mut Url) {
+    let is_tracking = |name: &str| {
+        let name = name.to_ascii_lowercase();
+        name.starts_with("utm_") || TRACKING_PARAMS.contains(&name.as_str())
+    };
+    if !url.query_pairs().any(|(name, _)| is_tracking(&name)) {
+        return;
+    }
+    let pairs: Vec<(String, String)> = url
+        .query_pairs()
+        .filter(|(name, _)| !is_tracking(name))
+        .map(|(name, value)| (name.into_owned(), value.into_owned()))
+        .collect();
+    if pairs.is_empty() {
+        url.set_query(None);
+    } else {
+        url.query_pairs_mut().clear().extend_pairs(pairs);
+    }
+}
+
+// Tries the url as given. If it is an https url the user didn't type a scheme for and
+// the secure connection fails, the site is tried again over plain http.
+pub fn scrape_url(
+    url: Url,
+    user_agent: &UserAgent,
+    task: &Task,
+    http_fallback: bool,
+) -> Result<ScrapeResult, Error> {
+    let err = match scrape(url.clone(), user_agent, task) {
+        Err(err) if http_fallback && url.scheme() == "https" && is_connection_error(&err) => err,
+        res => return res,
+    };
+    let mut http_url = url;
+    if http_url.set_scheme("http").is_err() {
+        return Err(err);
+    }
+    // If http doesn't work either, the https error is the more helpful one
+    let mut res = scrape(http_url, user_agent, task).map_err(|http_err| match http_err {
+        Error::Cancelled => http_err,
+        _ => err,
+    })?;
+    res.fallback_url = res.redirect_chain.first().cloned().unwrap_or_default();
+    Ok(res)
+}
+
+// Errors where the site might still be reachable over http
+fn is_connection_error(err: &Error) -> bool {
+    matches!(
+        err,
+        Error::Connect(_) | Error::Tls(_) | Error::Timeout | Error::Other(_)
+    )
+}
+
+fn scrape(url: Url, user_agent: &UserAgent, task: &Task) -> Result<ScrapeResult, Error> {
+    // Scrape with the same user agent the app will use, sites often serve different markup
+    let client = net::client(Some(user_agent.effective_string()))?;
+    let (resp, redirect_chain) = net::fetch(&client, &url, task)?;
+    net::check_status(&resp)?;
+    if !is_html(&resp) {
+        return Err(Error::NotHtml {
+            url: resp.url.clone(),
+            content_type: resp.content_type.clone().unwrap_or_default(),
+        });
+    }
+    let body = response_text(&resp);
+    // Relative links on the page are relative to where we ended up
+    let page_url = redirect_chain
+        .last()
+        .cloned()
+        .unwrap_or_else(|| url.clone());
 
     let html = scraper::Html::parse_document(&body);
 
-    let res = ScrapeResult::parse(url, html);
+    // A missing or broken manifest is not fatal, we fall back to the html head
+    let manifest = manifest_url(&page_url, &html).and_then(|manifest_url| {
+        let body = fetch_text(&client, &manifest_url, task).ok()?;
+        WebManifest::parse(&manifest_url, &body).ok()
+    });
+
+    let mut res = ScrapeResult::parse(page_url, html, manifest);
+
+    // The patterns are meant for what the user typed, redirects become suggestions
+    res.default_url_patterns = default_url_patterns(&url);
+    let mut suggestions = redirect_suggestions(&redirect_chain);
+    suggestions.append(&mut res.suggested_url_patterns);
+    res.suggested_url_patterns = filter_suggestions(suggestions, &res.default_url_patterns);
+    res.redirect_chain = redirect_chain.iter().map(Url::to_string).collect();
+    // Nothing to offer if what the user typed is already the best url
+    if res.resolved_url == url.as_str() {
+        res.resolved_url.clear();
+    }
+
+    // The implicit /favicon.ico is only a guess, make sure it exists before we pick it
+    let favicon_missing = res
+        .icon_candidates
+        .first()
+        .map(|icon| icon.source == IconSource::Favicon && !url_exists(&client, &icon.url, task))
+        .unwrap_or(false);
+    if favicon_missing {
+        res.icon_candidates.remove(0);
+        res.icon_url = res
+            .icon_candidates
+            .first()
+            .map(|icon| icon.url.clone())
+            .unwrap_or_default();
+    }
+    res.warnings = client.take_warnings();
 
     Ok(res)
 }
 
+// Decodes the body with the charset from the content type, defaulting to UTF-8
+fn response_text(resp: &cache::Response) -> String {
+    let encoding = resp
+        .content_type
+        .as_ref()
+        .and_then(|content_type| {
+            content_type
+                .split(';')
+                .filter_map(|param| {
+                    let param = param.trim();
+                    let pos = param.find('=')?;
+                    if param[..pos].trim().eq_ignore_ascii_case("charset") {
+                        Some(param[pos + 1..].trim().trim_matches('"'))
+                    } else {
+                        None
+                    }
+                })
+                .next()
+        })
+        .and_then(|charset| encoding_rs::Encoding::for_label(charset.as_bytes()))
+        .unwrap_or(encoding_rs::UTF_8);
+    encoding.decode(&resp.body).0.into_owned()
+}
+
+// Downloads instead of asking with HEAD, the icon will be needed anyway and is cached then
+fn url_exists(client: &net::Client, url: &str, task: &Task) -> bool {
+    Url::parse(url)
+        .ok()
+        .and_then(|url| net::fetch(client, &url, task).ok())
+        .map(|(resp, _)| resp.is_success())
+        .unwrap_or(false)
+}
+
+fn fetch_text(client: &net::Client, url: &Url, task: &Task) -> Result<String, Error> {
+    let (resp, _) = net::fetch(client, url, task)?;
+    net::check_status(&resp)?;
+    Ok(response_text(&resp))
+}
+
+// Servers that don't send a content type mostly serve html anyway
+fn is_html(resp: &cache::Response) -> bool {
+    match resp.content_type {
+        Some(ref content_type) => {
+            let mime = content_type.split(';').next().unwrap_or_default();
+            let mime = mime.trim().to_ascii_lowercase();
+            mime == "text/html" || mime == "application/xhtml+xml"
+        }
+        None => true,
+    }
+}
+
+pub fn default_url_patterns(url: &Url) -> Vec<String> {
+    if let Some(url::Host::Domain(domain)) = url.host() {
+        vec![
+            format!("https?://{}/*", domain),
+            format!("https?://*.{}/*", domain),
+        ]
+    } else {
+        Vec::default()
+    }
+}
+
+fn host_pattern(url: &Url) -> Option<String> {
+    match url.scheme() {
+        "http" | "https" => Some(format!("https?://{}/*", url.host_str()?)),
+        _ => None,
+    }
+}
+
+fn redirect_suggestions(chain: &[Url]) -> Vec<SuggestedPattern> {
+    chain
+        .windows(2)
+        .filter_map(|pair| {
+            Some(SuggestedPattern {
+                pattern: host_pattern(&pair[1])?,
+                reason: format!(
+                    "{} redirects here",
+                    pair[0].host_str().unwrap_or_else(|| pair[0].as_str())
+                ),
+            })
+        })
+        .collect()
+}
+
+// Drops duplicates and everything the default patterns already cover
+fn filter_suggestions(
+    suggestions: Vec<SuggestedPattern>,
+    default_patterns: &[String],
+) -> Vec<SuggestedPattern> {
+    let mut filtered: Vec<SuggestedPattern> = Vec::new();
+    for suggestion in suggestions {
+        let sample = suggestion
+            .pattern
+            .replace("https?://", "https://")
+            .replace('*', "");
+        let covered = patterns::find_match(default_patterns, &sample).is_some()
+            || filtered.iter().any(|s| s.pattern == suggestion.pattern);
+        if !covered {
+            filtered.push(suggestion);
+        }
+    }
+    filtered
+}
+
+fn manifest_url(url: &Url, html: &scraper::Html) -> Option<Url> {
+    let manifest_sel = scraper::Selector::parse("html > head > link[rel~='manifest']").unwrap();
+    let href = html
+        .select(&manifest_sel)
+        .next()
+        .and_then(|el| el.value().attr("href"))?;
+    if href.trim().is_empty() {
+        return None;
+    }
+    url.join(href.trim()).ok()
+}
+
+// Most of the result is only shown in the user interface
+#[cfg_attr(not(feature = "gui"), allow(dead_code))]
+pub struct SuggestedPattern {
+    pub pattern: String,
+    pub reason: String,
+}
+
+#[cfg_attr(not(feature = "gui"), allow(dead_code))]
 pub struct ScrapeResult {
     pub site_name: String,
+    pub short_name: String,
     pub title: String,
     pub theme_color: String,
+    pub background_color: String,
+    pub start_url: String,
+    pub scope: String,
     pub icon_url: String,
+    pub icon_candidates: Vec<IconCandidate>,
+    pub icons: Vec<ManifestIcon>,
     pub default_url_patterns: Vec<String>,
+    pub suggested_url_patterns: Vec<SuggestedPattern>,
+    pub redirect_chain: Vec<String>,
+    // Where the redirects ended
+    pub final_url: String,
+    pub canonical_url: String,
+    // Best url to launch the app with, see `resolve_url`
+    pub resolved_url: String,
+    // The http url that was scraped because https failed, empty if https worked
+    pub fallback_url: String,
+    // Problems that did not stop the scrape, e.g. the cache could not be written
+    pub warnings: Vec<String>,
 }
 
 impl ScrapeResult {
-    pub fn parse(url: Url, html: scraper::Html) -> Self {
+    pub fn parse(url: Url, html: scraper::Html, manifest: Option<WebManifest>) -> Self {
+        let manifest = manifest.unwrap_or_default();
         let title_sel = scraper::Selector::parse("html > head > title").unwrap();
         let title = html
             .select(&title_sel)
             .next()
-            .map(|el| el.inner_html())
+            .map(|el| site_title(&el.text().collect::<String>(), &url))
             .unwrap_or_default();
         let og_name_sel =
             scraper::Selector::parse("html > head > meta[property='og:site_name']").unwrap();
-        let site_name = html
-            .select(&og_name_sel)
-            .next()
-            .map(|el| el.value().attr("content").unwrap_or_default().to_owned())
+        let site_name = manifest
+            .name
+            .clone()
+            .filter(|name| !name.trim().is_empty())
+            .or_else(|| meta_content(&html, "application-name"))
+            .unwrap_or_else(|| {
+                html.select(&og_name_sel)
+                    .next()
+                    .map(|el| el.value().attr("content").unwrap_or_default().to_owned())
+                    .unwrap_or_default()
+            });
+        let site_name = clean_text(&site_name);
+        // Apple's home screen title is meant to be short, like the manifest's short_name
+        let short_name = manifest
+            .short_name
+            .clone()
+            .filter(|name| !name.trim().is_empty())
+            .or_else(|| meta_content(&html, "apple-mobile-web-app-title"))
+            .map(|name| clean_text(&name))
             .unwrap_or_default();
         let theme_color_sel =
             scraper::Selector::parse("html > head > meta[name='theme-color']").unwrap();
-        let theme_color = html
-            .select(&theme_color_sel)
-            .next()
-            .map(|el| el.value().attr("content").unwrap_or_default().to_owned())
-            .unwrap_or_default();
-        let icon_sel = scraper::Selector::parse("html > head > link[rel='icon']").unwrap();
-        let apple_icon_sel =
-            scraper::Selector::parse("html > head > link[rel='apple-touch-icon']").unwrap();
-        let mut icon_url = html
-            .select(&icon_sel)
-            .next()
-            .map(|el| el.value().attr("href").unwrap_or_default().to_owned())
+        let theme_color = manifest
+            .theme_color
+            .clone()
+            .filter(|color| !color.trim().is_empty())
             .unwrap_or_else(|| {
-                html.select(&apple_icon_sel)
+                html.select(&theme_color_sel)
                     .next()
-                    .map(|el| el.value().attr("href").unwrap_or_default().to_owned())
+                    .map(|el| el.value().attr("content").unwrap_or_default().to_owned())
                     .unwrap_or_default()
             });
-        if icon_url != "" {
-            if let Ok(url) = url.join(&icon_url) {
-                icon_url = url.to_string();
-            }
-        }
+        let icon_candidates = icons::icon_candidates(&url, &html, &manifest.icons);
+        let icon_url = icon_candidates
+            .first()
+            .map(|icon| icon.url.clone())
+            .unwrap_or_default();
 
-        let default_url_patterns = if let Some(url::Host::Domain(domain)) = url.host() {
-            vec![
-                format!("https?://{}/*", domain),
-                format!("https?://*.{}/*", domain),
-            ]
-        } else {
-            Vec::default()
-        };
+        let default_url_patterns = default_url_patterns(&url);
+        let suggested_url_patterns = link_suggestions(&url, &html);
+        let canonical_url = canonical_url(&url, &html);
+        let start_url = manifest.start_url.unwrap_or_default();
+        let resolved_url = resolve_url(&url, &start_url, canonical_url.as_ref());
 
         Self {
             site_name,
+            short_name,
             title,
             theme_color,
+            background_color: manifest.background_color.unwrap_or_default(),
+            start_url,
+            scope: manifest.scope.unwrap_or_default(),
             icon_url,
+            icon_candidates,
+            icons: manifest.icons,
             default_url_patterns,
+            suggested_url_patterns,
+            redirect_chain: vec![url.to_string()],
+            final_url: url.to_string(),
+            canonical_url: canonical_url.map(|url| url.to_string()).unwrap_or_default(),
+            resolved_url: resolved_url.to_string(),
+            fallback_url: String::new(),
+            warnings: Vec::new(),
+        }
+    }
+}
+
+fn meta_content(html: &scraper::Html, name: &str) -> Option<String> {
+    let meta_sel = scraper::Selector::parse("html > head > meta[name][content]").unwrap();
+    html.select(&meta_sel)
+        .find(|el| {
+            el.value()
+                .attr("name")
+                .is_some_and(|value| value.trim().eq_ignore_ascii_case(name))
+        })
+        .and_then(|el| el.value().attr("content"))
+        .map(clean_text)
+        .filter(|content| !content.is_empty())
+}
+
+// Titles often span several lines in the markup
+fn clean_text(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+// Separators between the page and the site in titles like "Inbox | Example Mail"
+const TITLE_SEPARATORS: &[&str] = &[" | ", " - ", " – ", " — ", " · ", " :: ", " » "];
+// Title parts that don't name anything
+const GENERIC_TITLE_PARTS: &[&str] = &[
+    "home",
+    "homepage",
+    "home page",
+    "index",
+    "main page",
+    "start",
+    "start page",
+    "welcome",
+];
+
+// Turns titles like "Example - Home" into "Example". If only one part of the title
+// looks like the domain, e.g. "Latest news | Example News" on example.com, that part
+// is the name of the site.
+fn site_title(title: &str, url: &Url) -> String {
+    let title = clean_text(title);
+    let separator = match TITLE_SEPARATORS.iter().find(|sep| title.contains(*sep)) {
+        Some(separator) => *separator,
+        None => return title,
+    };
+    let parts: Vec<&str> = title
+        .split(separator)
+        .map(str::trim)
+        .filter(|part| {
+            !part.is_empty() && !GENERIC_TITLE_PARTS.contains(&part.to_lowercase().as_str())
+        })
+        .collect();
+    if parts.is_empty() {
+        return title;
+    }
+    let label = match domain_label(url) {
+        Some(label) => label,
+        None => return parts.join(separator),
+    };
+    let normalized = |part: &str| -> String {
+        part.chars()
+            .filter(|c| c.is_alphanumeric())
+            .flat_map(char::to_lowercase)
+            .collect()
+    };
+    // "GitHub: Let's build · GitHub" mentions the domain twice, the exact match wins
+    let exact: Vec<&str> = parts
+        .iter()
+        .filter(|part| normalized(part) == label)
+        .cloned()
+        .collect();
+    let named: Vec<&str> = parts
+        .iter()
+        .filter(|part| normalized(part).contains(&label))
+        .cloned()
+        .collect();
+    match (exact.as_slice(), named.as_slice()) {
+        ([name], _) | (_, [name]) => (*name).to_owned(),
+        _ => parts.join(separator),
+    }
+}
+
+// Most distinctive label of the host, e.g. "example" for www.example.co.uk
+fn domain_label(url: &Url) -> Option<String> {
+    let domain = match url.host() {
+        Some(url::Host::Domain(domain)) => domain.to_ascii_lowercase(),
+        _ => return None,
+    };
+    let mut labels: Vec<&str> = domain.split('.').collect();
+    if labels.len() > 1 {
+        labels.pop();
+    }
+    labels
+        .into_iter()
+        .filter(|label| *label != "www" && *label != "m")
+        .max_by_key(|label| label.len())
+        .map(|label| label.replace('-', ""))
+}
+
+fn canonical_url(url: &Url, html: &scraper::Html) -> Option<Url> {
+    let link_sel = scraper::Selector::parse("link[rel][href]").unwrap();
+    html.select(&link_sel)
+        .find(|el| {
+            el.value()
+                .attr("rel")
+                .unwrap_or_default()
+                .split_whitespace()
+                .any(|rel| rel.eq_ignore_ascii_case("canonical"))
+        })
+        .and_then(|el| url.join(el.value().attr("href")?.trim()).ok())
+}
+
+// The manifest's start_url is what the site wants apps to open, the canonical url is
+// the preferred address of the page. Both are only trusted on the host we ended up on,
+// canonical links often point to a different site, e.g. for syndicated content.
+fn resolve_url(url: &Url, start_url: &str, canonical_url: Option<&Url>) -> Url {
+    let same_site = |other: &Url| {
+        (other.scheme() == "https" || other.scheme() == "http")
+            && other.host_str() == url.host_str()
+    };
+    // Manifests love to tag their start_url with utm_source=homescreen and the like
+    Url::parse(start_url)
+        .ok()
+        .filter(|start_url| same_site(start_url))
+        .map(|mut start_url| {
+            strip_tracking_params(&mut start_url);
+            start_url
+        })
+        .or_else(|| {
+            canonical_url
+                .filter(|canonical| same_site(canonical))
+                .cloned()
+        })
+        .unwrap_or_else(|| url.clone())
+}
+
+// Login flows often leave the site, e.g. to an OAuth provider or a separate accounts domain
+fn link_suggestions(url: &Url, html: &scraper::Html) -> Vec<SuggestedPattern> {
+    let mut suggestions = Vec::new();
+    let mut add = |target: Url, reason: String| {
+        if target.host_str() == url.host_str() {
+            return;
+        }
+        if let Some(pattern) = host_pattern(&target) {
+            suggestions.push(SuggestedPattern { pattern, reason });
+        }
+    };
+
+    let form_sel = scraper::Selector::parse("form[action]").unwrap();
+    for el in html.select(&form_sel) {
+        let action = el.value().attr("action").unwrap_or_default().trim();
+        if let Ok(target) = url.join(action) {
+            let reason = format!(
+                "A form on the page submits to {}",
+                target.host_str().unwrap_or_default()
+            );
+            add(target, reason);
+        }
+    }
+
+    let link_sel = scraper::Selector::parse("a[href]").unwrap();
+    for el in html.select(&link_sel) {
+        let href = el.value().attr("href").unwrap_or_default().trim();
+        let target = match url.join(href) {
+            Ok(target) => target,
+            Err(_) => continue,
+        };
+        if is_login_url(&target) {
+            let reason = format!(
+                "The page links to a login at {}",
+                target.host_str().unwrap_or_default()
+            );
+            add(target, reason);
         }
     }
+
+    suggestions
+}
+
+// Whole path segments only, "sso" must not turn /lessons into a login
+fn is_login_url(url: &Url) -> bool {
+    let login_words = [
+        "oauth",
+        "openid",
+        "login",
+        "signin",
+        "sign-in",
+        "sso",
+        "authorize",
+    ];
+    // Allows suffixes like oauth2, login.php or sso-callback
+    let is_login_segment = |segment: &str| {
+        let segment = segment.to_ascii_lowercase();
+        login_words
+            .iter()
+            .any(|word| match segment.strip_prefix(word) {
+                Some(rest) => !rest.starts_with(|c: char| c.is_ascii_alphabetic()),
+                None => false,
+            })
+    };
+    let login_path = url
+        .path_segments()
+        .is_some_and(|mut segments| segments.any(is_login_segment));
+    let oauth_query = url
+        .query_pairs()
+        .any(|(key, _)| key == "client_id" || key == "redirect_uri");
+    login_path || oauth_query
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn site_title_strips_page_and_generic_parts() {
+        let title = |title: &str, url: &str| site_title(title, &Url::parse(url).unwrap());
+        assert_eq!(
+            title("Tom & Jerry - Home", "https://tom.example/"),
+            "Tom & Jerry"
+        );
+        assert_eq!(
+            title("  Hacker\n   News ", "https://news.ycombinator.com/"),
+            "Hacker News"
+        );
+        assert_eq!(
+            title("Latest news | BBC News", "https://www.bbc.co.uk/"),
+            "BBC News"
+        );
+        assert_eq!(
+            title("GitHub: Let's build · GitHub", "https://github.com/"),
+            "GitHub"
+        );
+        assert_eq!(title("Home | Example", "https://example.org/"), "Example");
+        assert_eq!(
+            title("Inbox - Mail", "https://example.org/"),
+            "Inbox - Mail"
+        );
+        assert_eq!(title("Home", "https://example.org/"), "Home");
+    }
+
+    #[test]
+    fn title_entities_are_decoded() {
+        let html = scraper::Html::parse_document(
+            "<html><head><title>Tom &amp; Jerry\n  &#8211; Cartoons</title></head></html>",
+        );
+        let url = Url::parse("https://example.org/").unwrap();
+        let res = ScrapeResult::parse(url, html, None);
+        assert_eq!(res.title, "Tom & Jerry – Cartoons");
+    }
+
+    #[test]
+    fn app_name_meta_tags_are_used() {
+        let html = scraper::Html::parse_document(
+            r#"<html><head>
+            <meta name="application-name" content=" Example  Mail ">
+            <meta name="apple-mobile-web-app-title" content="Mail">
+            <meta property="og:site_name" content="Example">
+            </head></html>"#,
+        );
+        let url = Url::parse("https://mail.example.org/").unwrap();
+        let res = ScrapeResult::parse(url, html, None);
+        assert_eq!(res.site_name, "Example Mail");
+        assert_eq!(res.short_name, "Mail");
+    }
+
+    #[test]
+    fn login_urls_are_matched_by_path_segment() {
+        let is_login = |url: &str| is_login_url(&Url::parse(url).unwrap());
+        assert!(is_login("https://accounts.example.com/login"));
+        assert!(is_login("https://example.com/oauth2/authorize"));
+        assert!(is_login("https://example.com/login.php"));
+        assert!(is_login("https://example.com/auth/sso-callback"));
+        assert!(is_login("https://example.com/app?client_id=1"));
+        assert!(!is_login("https://example.com/professor"));
+        assert!(!is_login("https://example.com/lessons"));
+        assert!(!is_login("https://example.com/blog/loginsights"));
+    }
 }
@@ -0,0 +1,234 @@
+use std::cmp::Reverse;
+
+use url::Url;
+
+use crate::webmanifest::ManifestIcon;
+
+// Launchers render icons at roughly this size, anything smaller looks blurry
+const MIN_LAUNCHER_SIZE: u32 = 192;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum IconSource {
+    Icon,
+    AppleTouchIcon,
+    MaskIcon,
+    Manifest,
+    Favicon,
+}
+
+#[derive(Debug, Clone)]
+pub struct IconCandidate {
+    pub url: String,
+    pub source: IconSource,
+    pub mime_type: String,
+    // Largest declared size as (width, height)
+    pub size: Option<(u32, u32)>,
+    pub any_size: bool,
+    pub monochrome: bool,
+}
+
+impl IconCandidate {
+    fn new(url: String, source: IconSource, sizes: Option<&str>, mime_type: Option<&str>) -> Self {
+        let sizes = sizes.unwrap_or_default();
+        let any_size = sizes
+            .split_whitespace()
+            .any(|s| s.eq_ignore_ascii_case("any"));
+        let size = sizes
+            .split_whitespace()
+            .filter_map(parse_size)
+            .max_by_key(|(w, h)| u64::from(*w) * u64::from(*h));
+        Self {
+            url,
+            source,
+            mime_type: mime_type.unwrap_or_default().trim().to_ascii_lowercase(),
+            size,
+            any_size,
+            monochrome: false,
+        }
+    }
+
+    pub fn is_svg(&self) -> bool {
+        self.mime_type == "image/svg+xml"
+            || Url::parse(&self.url)
+                .map(|url| url.path().to_ascii_lowercase().ends_with(".svg"))
+                .unwrap_or(false)
+    }
+
+    fn is_square(&self) -> bool {
+        self.size.map(|(w, h)| w == h).unwrap_or(true)
+    }
+
+    // Size we expect the image to have, taking the usual defaults into account
+    fn effective_size(&self) -> u32 {
+        match (self.size, self.source) {
+            (Some((w, h)), _) => w.min(h),
+            (None, IconSource::AppleTouchIcon) => 180,
+            (None, IconSource::Favicon) => 16,
+            (None, _) => 0,
+        }
+    }
+
+    fn rank(&self) -> (u32, u32) {
+        let size = if self.is_svg() || self.any_size {
+            u32::MAX
+        } else {
+            self.effective_size()
+        };
+        (self.tier(), size)
+    }
+
+    fn tier(&self) -> u32 {
+        // Single color silhouettes only look right when tinted, never pick them over a real icon
+        if self.monochrome || self.source == IconSource::MaskIcon {
+            0
+        } else if self.source == IconSource::Favicon || !self.is_square() {
+            1
+        } else if self.is_svg() || self.any_size || self.effective_size() >= MIN_LAUNCHER_SIZE {
+            4
+        } else if self.effective_size() > 0 {
+            3
+        } else {
+            2
+        }
+    }
+}
+
+fn is_http(url: &Url) -> bool {
+    url.scheme() == "http" || url.scheme() == "https"
+}
+
+fn parse_size(size: &str) -> Option<(u32, u32)> {
+    let mut parts = size.splitn(2, |c| c == 'x' || c == 'X');
+    let w = parts.next()?.parse().ok()?;
+    let h = parts.next()?.parse().ok()?;
+    Some((w, h))
+}
+
+pub fn icon_candidates(
+    url: &Url,
+    html: &scraper::Html,
+    manifest_icons: &[ManifestIcon],
+) -> Vec<IconCandidate> {
+    let mut candidates = Vec::new();
+
+    let link_sel = scraper::Selector::parse("html > head > link[rel][href]").unwrap();
+    for el in html.select(&link_sel) {
+        let el = el.value();
+        let href = el.attr("href").unwrap_or_default().trim();
+        let rel = el.attr("rel").unwrap_or_default().to_ascii_lowercase();
+        let rels = rel.split_whitespace().collect::<Vec<_>>();
+        let source = if rels.contains(&"mask-icon") {
+            IconSource::MaskIcon
+        } else if rels.iter().any(|rel| rel.starts_with("apple-touch-icon")) {
+            IconSource::AppleTouchIcon
+        } else if rels.contains(&"icon") {
+            IconSource::Icon
+        } else {
+            continue;
+        };
+        if href.is_empty() {
+            continue;
+        }
+        // Only what a browser would load, not e.g. file:// urls
+        if let Some(icon_url) = url.join(href).ok().filter(is_http) {
+            candidates.push(IconCandidate::new(
+                icon_url.to_string(),
+                source,
+                el.attr("sizes"),
+                el.attr("type"),
+            ));
+        }
+    }
+
+    for icon in manifest_icons {
+        let mut candidate = IconCandidate::new(
+            icon.src.clone(),
+            IconSource::Manifest,
+            icon.sizes.as_deref(),
+            icon.mime_type.as_deref(),
+        );
+        candidate.monochrome = icon
+            .purpose
+            .as_ref()
+            .map(|purpose| {
+                purpose
+                    .split_whitespace()
+                    .all(|p| p.eq_ignore_ascii_case("monochrome"))
+            })
+            .unwrap_or(false);
+        candidates.push(candidate);
+    }
+
+    if let Ok(favicon_url) = url.join("/favicon.ico") {
+        let favicon_url = favicon_url.to_string();
+        if !candidates.iter().any(|c| c.url == favicon_url) {
+            candidates.push(IconCandidate::new(
+                favicon_url,
+                IconSource::Favicon,
+                None,
+                Some("image/x-icon"),
+            ));
+        }
+    }
+
+    // Stable sort, so declaration order decides between equally good icons
+    candidates.sort_by_key(|c| Reverse(c.rank()));
+    candidates
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn candidates(head: &str, manifest_icons: &[ManifestIcon]) -> Vec<String> {
+        let url = Url::parse("https://example.com/").unwrap();
+        let html = scraper::Html::parse_document(&format!("<html><head>{}</head></html>", head));
+        icon_candidates(&url, &html, manifest_icons)
+            .into_iter()
+            .map(|c| c.url.trim_start_matches("https://example.com/").to_owned())
+            .collect()
+    }
+
+    #[test]
+    fn candidates_are_ranked_by_usefulness() {
+        let head = r#"
+            <link rel="mask-icon" href="mask.svg">
+            <link rel="icon" href="wide.png" sizes="300x150">
+            <link rel="icon" href="small.png" sizes="32x32">
+            <link rel="apple-touch-icon" href="touch.png">
+            <link rel="icon" href="large.png" sizes="192x192">
+            <link rel="icon" href="vector.svg" type="image/svg+xml">
+            <link rel="icon" href="file:///etc/passwd" sizes="512x512">
+        "#;
+        assert_eq!(
+            candidates(head, &[]),
+            vec![
+                "vector.svg",
+                "large.png",
+                "touch.png",
+                "small.png",
+                "wide.png",
+                "favicon.ico",
+                "mask.svg",
+            ]
+        );
+    }
+
+    #[test]
+    fn monochrome_manifest_icons_come_last() {
+        let icon = |src: &str, purpose: Option<&str>| ManifestIcon {
+            src: format!("https://example.com/{}", src),
+            sizes: Some("512x512".to_owned()),
+            mime_type: None,
+            purpose: purpose.map(str::to_owned),
+        };
+        let manifest_icons = vec![
+            icon("monochrome.png", Some("monochrome")),
+            icon("maskable.png", Some("maskable any")),
+        ];
+        assert_eq!(
+            candidates("", &manifest_icons),
+            vec!["maskable.png", "favicon.ico", "monochrome.png"]
+        );
+    }
+}
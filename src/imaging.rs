@@ -0,0 +1,743 @@
+use std::io::Cursor;
+
+use image::imageops::{self, FilterType};
+use image::{DynamicImage, GenericImageView, ImageFormat, ImageOutputFormat, Rgba, RgbaImage};
+use serde::{Deserialize, Serialize};
+
+// Size of the PNG embedded in the package, big enough for the launcher on high density screens
+pub const ICON_SIZE: u32 = 256;
+// Favicons up to this size are pixel art, smoothing them while upscaling only blurs them
+const PIXEL_ART_SIZE: u32 = 64;
+// Larger images are refused before decoding, a forged header could claim gigabytes otherwise
+const MAX_DIMENSION: u32 = 4096;
+
+pub enum IconData {
+    Svg(Vec<u8>),
+    Png(Vec<u8>),
+}
+
+impl IconData {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            IconData::Svg(_) => "svg",
+            IconData::Png(_) => "png",
+        }
+    }
+
+    pub fn bytes(&self) -> &[u8] {
+        match self {
+            IconData::Svg(bytes) | IconData::Png(bytes) => bytes,
+        }
+    }
+}
+
+// Turns a downloaded icon into something the launcher can show. SVGs are kept as they are,
+// everything else is decoded and stored as a square PNG of ICON_SIZE.
+pub fn process_icon(bytes: &[u8], content_type: Option<&str>) -> Result<IconData, String> {
+    if is_svg(bytes, content_type) {
+        return Ok(IconData::Svg(bytes.to_vec()));
+    }
+    let image = decode(bytes)?;
+    let mut png = Vec::new();
+    square(&image)
+        .write_to(&mut png, ImageOutputFormat::Png)
+        .map_err(|err| err.to_string())?;
+    Ok(IconData::Png(png))
+}
+
+fn is_svg(bytes: &[u8], content_type: Option<&str>) -> bool {
+    if content_type.is_some_and(|mime| mime.contains("image/svg")) {
+        return true;
+    }
+    // Servers often send SVGs as text/plain or application/octet-stream, look at the content
+    let head = String::from_utf8_lossy(&bytes[..bytes.len().min(1024)]).to_ascii_lowercase();
+    let head = head.trim_start_matches('\u{feff}').trim_start();
+    (head.starts_with("<?xml") || head.starts_with("<svg") || head.starts_with("<!doctype svg"))
+        && head.contains("<svg")
+}
+
+// The format is sniffed from the content, the content type and url extension are too often wrong
+fn decode(bytes: &[u8]) -> Result<DynamicImage, String> {
+    let format = image::guess_format(bytes).map_err(|_| "Unknown image format".to_owned())?;
+    match format {
+        // The ICO decoder picks the largest image of the file, GIFs use their first frame
+        ImageFormat::Ico
+        | ImageFormat::Png
+        | ImageFormat::Jpeg
+        | ImageFormat::Gif
+        | ImageFormat::WebP => {
+            let (width, height) = image::io::Reader::with_format(Cursor::new(bytes), format)
+                .into_dimensions()
+                .map_err(|err| err.to_string())?;
+            if width > MAX_DIMENSION || height > MAX_DIMENSION {
+                return Err(format!("The image is too large ({}x{})", width, height));
+            }
+            image::load_from_memory_with_format(bytes, format).map_err(|err| err.to_string())
+        }
+        format => Err(format!("Unsupported image format {:?}", format)),
+    }
+}
+
+// Centers the image on a transparent square and scales it to ICON_SIZE
+fn square(image: &DynamicImage) -> DynamicImage {
+    let (width, height) = image.dimensions();
+    let side = width.max(height);
+    let mut canvas = RgbaImage::new(side, side);
+    imageops::overlay(
+        &mut canvas,
+        &image.to_rgba(),
+        (side - width) / 2,
+        (side - height) / 2,
+    );
+    let filter = if side <= PIXEL_ART_SIZE {
+        FilterType::Nearest
+    } else {
+        FilterType::Lanczos3
+    };
+    DynamicImage::ImageRgba8(imageops::resize(&canvas, ICON_SIZE, ICON_SIZE, filter))
+}
+
+// Exponent of the superellipse used as mask, 2 would be a circle
+const SQUIRCLE_EXPONENT: f32 = 5.0;
+const SQUIRCLE_POINTS: u32 = 128;
+// Subpixel samples per axis when masking raster icons, for smooth edges
+const MASK_SAMPLES: u32 = 4;
+const MAX_PADDING: u32 = 40;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IconMask {
+    #[default]
+    None,
+    Squircle,
+}
+
+impl IconMask {
+    pub fn from_name(name: &str) -> Self {
+        match name {
+            "squircle" => IconMask::Squircle,
+            _ => IconMask::None,
+        }
+    }
+}
+
+// How the icon is placed on the launcher tile
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct IconStyle {
+    pub background: bool,
+    // The theme color of the shortcut is used if not set
+    pub background_color: Option<String>,
+    // Space around the icon in percent of the icon size
+    pub padding: u32,
+    pub mask: IconMask,
+}
+
+impl IconStyle {
+    fn is_plain(&self) -> bool {
+        !self.background && self.padding == 0 && self.mask == IconMask::None
+    }
+}
+
+// The style is cosmetic, an icon that can't be styled is better than no package at all.
+// A color that can't be parsed, e.g. a CSS name from an imported shortcut, leaves out the
+// background, an SVG that can't be nested is used unstyled. What was left out is added
+// to `warnings`.
+pub fn compose(
+    icon: IconData,
+    style: &IconStyle,
+    theme_color: &str,
+    warnings: &mut Vec<String>,
+) -> IconData {
+    if style.is_plain() {
+        return icon;
+    }
+    let background = if style.background {
+        let color = style
+            .background_color
+            .as_ref()
+            .map(|color| color.trim())
+            .filter(|color| !color.is_empty())
+            .unwrap_or(theme_color);
+        match parse_color(color) {
+            Ok(color) => Some(color),
+            Err(err) => {
+                warnings.push(format!("Leaving out the icon background: {}", err));
+                None
+            }
+        }
+    } else {
+        None
+    };
+    let inner = ICON_SIZE * (100 - 2 * style.padding.min(MAX_PADDING)) / 100;
+    let composed = match icon {
+        IconData::Png(ref png) => {
+            compose_png(png, style.mask, background, inner).map(IconData::Png)
+        }
+        IconData::Svg(ref svg) => {
+            compose_svg(svg, style.mask, background, inner).map(IconData::Svg)
+        }
+    };
+    composed.unwrap_or_else(|err| {
+        warnings.push(format!("Using the icon without style: {}", err));
+        icon
+    })
+}
+
+fn compose_png(
+    png: &[u8],
+    mask: IconMask,
+    background: Option<Rgba<u8>>,
+    inner: u32,
+) -> Result<Vec<u8>, String> {
+    let icon = image::load_from_memory_with_format(png, ImageFormat::Png)
+        .map_err(|err| err.to_string())?;
+    let mut canvas = RgbaImage::from_pixel(
+        ICON_SIZE,
+        ICON_SIZE,
+        background.unwrap_or(Rgba([0, 0, 0, 0])),
+    );
+    let icon = imageops::resize(&icon.to_rgba(), inner, inner, FilterType::Lanczos3);
+    let offset = (ICON_SIZE - inner) / 2;
+    imageops::overlay(&mut canvas, &icon, offset, offset);
+
+    if mask == IconMask::Squircle {
+        for (x, y, pixel) in canvas.enumerate_pixels_mut() {
+            let alpha = f32::from(pixel[3]) * squircle_coverage(x, y);
+            pixel[3] = alpha.round() as u8;
+        }
+    }
+
+    let mut png = Vec::new();
+    DynamicImage::ImageRgba8(canvas)
+        .write_to(&mut png, ImageOutputFormat::Png)
+        .map_err(|err| err.to_string())?;
+    Ok(png)
+}
+
+fn inside_squircle(x: f32, y: f32) -> bool {
+    let radius = ICON_SIZE as f32 / 2.0;
+    let dx = ((x - radius) / radius).abs();
+    let dy = ((y - radius) / radius).abs();
+    dx.powf(SQUIRCLE_EXPONENT) + dy.powf(SQUIRCLE_EXPONENT) <= 1.0
+}
+
+// Share of the pixel that lies inside the mask
+fn squircle_coverage(x: u32, y: u32) -> f32 {
+    let step = 1.0 / MASK_SAMPLES as f32;
+    let inside = (0..MASK_SAMPLES * MASK_SAMPLES)
+        .filter(|i| {
+            let sx = x as f32 + ((i % MASK_SAMPLES) as f32 + 0.5) * step;
+            let sy = y as f32 + ((i / MASK_SAMPLES) as f32 + 0.5) * step;
+            inside_squircle(sx, sy)
+        })
+        .count();
+    inside as f32 / (MASK_SAMPLES * MASK_SAMPLES) as f32
+}
+
+fn squircle_path() -> String {
+    let radius = ICON_SIZE as f32 / 2.0;
+    let points = (0..SQUIRCLE_POINTS)
+        .map(|i| {
+            let angle = i as f32 * 2.0 * std::f32::consts::PI / SQUIRCLE_POINTS as f32;
+            let (sin, cos) = angle.sin_cos();
+            let x = radius + radius * cos.signum() * cos.abs().powf(2.0 / SQUIRCLE_EXPONENT);
+            let y = radius + radius * sin.signum() * sin.abs().powf(2.0 / SQUIRCLE_EXPONENT);
+            format!("{:.2},{:.2}", x, y)
+        })
+        .collect::<Vec<_>>();
+    format!("M{}Z", points.join(" L"))
+}
+
+// Nests the content of the original SVG in a new one, so that it stays a vector graphic
+fn compose_svg(
+    svg: &[u8],
+    mask: IconMask,
+    background: Option<Rgba<u8>>,
+    inner: u32,
+) -> Result<Vec<u8>, String> {
+    let svg = String::from_utf8_lossy(svg);
+    let start = root_start(&svg)?;
+    let tag_end = tag_end(&svg, start).ok_or("Unterminated svg element")?;
+    let end = svg.rfind("</svg>").ok_or("Unterminated svg element")?;
+    if end < tag_end {
+        return Err("The svg element is empty".to_owned());
+    }
+    let attributes = parse_attributes(&svg[start + "<svg".len()..tag_end]);
+    let attribute = |name: &str| {
+        attributes
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+    };
+
+    let (view_x, view_y, view_width, view_height) = match attribute("viewBox") {
+        Some(view_box) => {
+            let numbers = view_box
+                .split(|c: char| c.is_whitespace() || c == ',')
+                .filter(|s| !s.is_empty())
+                .filter_map(|s| s.parse::<f32>().ok())
+                .collect::<Vec<_>>();
+            match numbers.as_slice() {
+                [x, y, width, height] => (*x, *y, *width, *height),
+                _ => return Err(format!("Invalid viewBox \"{}\"", view_box)),
+            }
+        }
+        None => {
+            let length = |name: &str| attribute(name).and_then(parse_length);
+            match (length("width"), length("height")) {
+                (Some(width), Some(height)) => (0.0, 0.0, width, height),
+                _ => return Err("The SVG has neither a viewBox nor a size".to_owned()),
+            }
+        }
+    };
+    if view_width <= 0.0 || view_height <= 0.0 {
+        return Err("The SVG has no size".to_owned());
+    }
+
+    let inner = inner as f32;
+    let scale = inner / view_width.max(view_height);
+    let offset_x = (ICON_SIZE as f32 - view_width * scale) / 2.0;
+    let offset_y = (ICON_SIZE as f32 - view_height * scale) / 2.0;
+
+    // Namespaces and inherited presentation attributes of the old root have to be kept
+    let mut namespaces = String::new();
+    let mut inherited = String::new();
+    for (key, value) in &attributes {
+        // Values may have been single quoted
+        let attribute = format!(" {}=\"{}\"", key, value.replace('"', "&quot;"));
+        if key.starts_with("xmlns:") {
+            namespaces.push_str(&attribute);
+        } else if ![
+            "xmlns",
+            "width",
+            "height",
+            "viewBox",
+            "x",
+            "y",
+            "version",
+            "id",
+            "preserveAspectRatio",
+        ]
+        .contains(&key.as_str())
+        {
+            inherited.push_str(&attribute);
+        }
+    }
+
+    let mut composed = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\"{} width=\"{size}\" height=\"{size}\" viewBox=\"0 0 {size} {size}\">\n",
+        namespaces,
+        size = ICON_SIZE
+    );
+    if mask == IconMask::Squircle {
+        composed.push_str(&format!(
+            "<defs><clipPath id=\"webber-mask\"><path d=\"{}\"/></clipPath></defs>\n<g clip-path=\"url(#webber-mask)\">\n",
+            squircle_path()
+        ));
+    } else {
+        composed.push_str("<g>\n");
+    }
+    if let Some(Rgba([r, g, b, a])) = background {
+        composed.push_str(&format!(
+            "<rect width=\"{size}\" height=\"{size}\" fill=\"#{:02x}{:02x}{:02x}\" fill-opacity=\"{:.3}\"/>\n",
+            r,
+            g,
+            b,
+            f32::from(a) / 255.0,
+            size = ICON_SIZE
+        ));
+    }
+    composed.push_str(&format!(
+        "<g transform=\"translate({:.3} {:.3}) scale({:.5}) translate({:.3} {:.3})\"{}>\n{}\n</g>\n</g>\n</svg>\n",
+        offset_x,
+        offset_y,
+        scale,
+        -view_x,
+        -view_y,
+        inherited,
+        &svg[tag_end + 1..end]
+    ));
+    Ok(composed.into_bytes())
+}
+
+// Start of the root element, after the XML declaration and comments. A DOCTYPE may declare
+// entities that the content refers to, they would be lost when nesting the content.
+fn root_start(svg: &str) -> Result<usize, String> {
+    let mut pos = 0;
+    loop {
+        let rest = svg[pos..].trim_start_matches(|c: char| c.is_whitespace() || c == '\u{feff}');
+        pos = svg.len() - rest.len();
+        let skip_to = if rest.starts_with("<?") {
+            "?>"
+        } else if rest.starts_with("<!--") {
+            "-->"
+        } else if rest
+            .get(..9)
+            .is_some_and(|s| s.eq_ignore_ascii_case("<!doctype"))
+        {
+            return Err("SVGs with a DOCTYPE are not supported".to_owned());
+        } else if rest.starts_with("<svg")
+            && rest[4..].starts_with(|c: char| c.is_whitespace() || c == '>' || c == '/')
+        {
+            return Ok(pos);
+        } else {
+            return Err("No svg element found".to_owned());
+        };
+        pos += rest
+            .find(skip_to)
+            .ok_or("Unterminated XML declaration or comment")?
+            + skip_to.len();
+    }
+}
+
+// Position of the '>' closing the tag at `start`, quoted attribute values may contain one
+fn tag_end(svg: &str, start: usize) -> Option<usize> {
+    let mut quote = None;
+    for (i, c) in svg[start..].char_indices() {
+        match quote {
+            Some(q) if c == q => quote = None,
+            Some(_) => {}
+            None if c == '"' || c == '\'' => quote = Some(c),
+            None if c == '>' => return Some(start + i),
+            None => {}
+        }
+    }
+    None
+}
+
+fn parse_attributes(tag: &str) -> Vec<(String, String)> {
+    let mut attributes = Vec::new();
+    let mut rest = tag.trim_end_matches('/');
+    loop {
+        rest = rest.trim_start();
+        let name_end = match rest.find(|c: char| c == '=' || c.is_whitespace()) {
+            Some(pos) => pos,
+            None => break,
+        };
+        let name = &rest[..name_end];
+        rest = rest[name_end..].trim_start();
+        if !rest.starts_with('=') {
+            continue;
+        }
+        rest = rest[1..].trim_start();
+        let quote = match rest.chars().next() {
+            Some(quote) if quote == '"' || quote == '\'' => quote,
+            _ => break,
+        };
+        let value_end = match rest[1..].find(quote) {
+            Some(pos) => pos + 1,
+            None => break,
+        };
+        attributes.push((name.to_owned(), rest[1..value_end].to_owned()));
+        rest = &rest[value_end + 1..];
+    }
+    attributes
+}
+
+// Sizes like "48" or "48px", relative units can't be resolved without a viewport
+fn parse_length(value: &str) -> Option<f32> {
+    value.trim().trim_end_matches("px").parse().ok()
+}
+
+// Accepts the formats of the color field: #rgb, #rrggbb and Qt's #aarrggbb
+pub fn parse_color(color: &str) -> Result<Rgba<u8>, String> {
+    let invalid = || format!("Invalid color \"{}\"", color);
+    let hex = color.trim().trim_start_matches('#');
+    if !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(invalid());
+    }
+    let channel = |s: &str| u8::from_str_radix(s, 16).map_err(|_| invalid());
+    match hex.len() {
+        3 => {
+            let digit = |i: usize| channel(&hex[i..=i]).map(|v| v * 17);
+            Ok(Rgba([digit(0)?, digit(1)?, digit(2)?, 255]))
+        }
+        6 => Ok(Rgba([
+            channel(&hex[0..2])?,
+            channel(&hex[2..4])?,
+            channel(&hex[4..6])?,
+            255,
+        ])),
+        8 => Ok(Rgba([
+            channel(&hex[2..4])?,
+            channel(&hex[4..6])?,
+            channel(&hex[6..8])?,
+            channel(&hex[0..2])?,
+        ])),
+        _ => Err(invalid()),
+    }
+}
+
+// Background colors for letter avatars of sites without a usable theme color
+const AVATAR_COLORS: [&str; 8] = [
+    "#e95420", "#77216f", "#19b6ee", "#3eb34f", "#ed3146", "#f99b11", "#335280", "#5e2750",
+];
+
+// Square SVG showing up to two initials of the app over its theme color
+pub fn letter_avatar(name: &str, host: &str, theme_color: &str) -> Vec<u8> {
+    let background = match parse_color(theme_color) {
+        Ok(color) => color,
+        Err(_) => {
+            // FNV-1a, the same host always gets the same color
+            let hash = host.bytes().fold(0x811c_9dc5_u32, |hash, b| {
+                (hash ^ u32::from(b)).wrapping_mul(0x0100_0193)
+            });
+            let color = AVATAR_COLORS[hash as usize % AVATAR_COLORS.len()];
+            parse_color(color).expect("Invalid avatar color")
+        }
+    };
+    let Rgba([r, g, b, _]) = background;
+    // Relative luminance decides whether dark or light letters are easier to read
+    let luminance = 0.2126 * f32::from(r) + 0.7152 * f32::from(g) + 0.0722 * f32::from(b);
+    let foreground = if luminance > 160.0 {
+        "#333333"
+    } else {
+        "#ffffff"
+    };
+
+    let letters = initials(name)
+        .or_else(|| initials(host.split('.').next().unwrap_or_default()))
+        .unwrap_or_else(|| "?".to_owned());
+    let font_size = if letters.chars().count() > 1 {
+        ICON_SIZE * 2 / 5
+    } else {
+        ICON_SIZE / 2
+    };
+
+    format!(
+        r##"<svg xmlns="http://www.w3.org/2000/svg" width="{size}" height="{size}" viewBox="0 0 {size} {size}">
+<rect width="{size}" height="{size}" fill="#{:02x}{:02x}{:02x}"/>
+<text x="{center}" y="{baseline}" fill="{}" font-family="Ubuntu, sans-serif" font-size="{}" font-weight="500" text-anchor="middle">{}</text>
+</svg>
+"##,
+        r,
+        g,
+        b,
+        foreground,
+        font_size,
+        letters,
+        size = ICON_SIZE,
+        center = ICON_SIZE / 2,
+        // Capital letters are roughly 0.7 em high, this centers them vertically
+        baseline = ICON_SIZE / 2 + font_size * 7 / 20,
+    )
+    .into_bytes()
+}
+
+// First letters of the first two words, e.g. "Tom & Jerry" -> "TJ"
+fn initials(name: &str) -> Option<String> {
+    let initials = name
+        .split(|c: char| !c.is_alphanumeric())
+        .filter_map(|word| word.chars().next())
+        .take(2)
+        .flat_map(char::to_uppercase)
+        .collect::<String>();
+    if initials.is_empty() {
+        None
+    } else {
+        Some(initials)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Signature and header of a PNG, enough to read its dimensions
+    fn png_header(width: u32, height: u32) -> Vec<u8> {
+        let mut png = b"\x89PNG\r\n\x1a\n".to_vec();
+        let chunk = |png: &mut Vec<u8>, kind: &[u8], data: &[u8]| {
+            png.extend_from_slice(&(data.len() as u32).to_be_bytes());
+            let mut crc = flate2::Crc::new();
+            crc.update(kind);
+            crc.update(data);
+            png.extend_from_slice(kind);
+            png.extend_from_slice(data);
+            png.extend_from_slice(&crc.sum().to_be_bytes());
+        };
+        let mut ihdr = Vec::new();
+        ihdr.extend_from_slice(&width.to_be_bytes());
+        ihdr.extend_from_slice(&height.to_be_bytes());
+        // 8 bit RGBA, no interlacing
+        ihdr.extend_from_slice(&[8, 6, 0, 0, 0]);
+        chunk(&mut png, b"IHDR", &ihdr);
+        chunk(&mut png, b"IDAT", &[]);
+        chunk(&mut png, b"IEND", &[]);
+        png
+    }
+
+    fn png(width: u32, height: u32) -> Vec<u8> {
+        let mut png = Vec::new();
+        DynamicImage::ImageRgba8(RgbaImage::new(width, height))
+            .write_to(&mut png, ImageOutputFormat::Png)
+            .unwrap();
+        png
+    }
+
+    #[test]
+    fn huge_images_are_refused_before_decoding() {
+        let err = process_icon(&png_header(5000, 16), None).err().unwrap();
+        assert!(err.contains("too large"), "{}", err);
+    }
+
+    #[test]
+    fn raster_icons_are_squared() {
+        let icon = process_icon(&png(64, 32), Some("image/png")).unwrap();
+        let image = image::load_from_memory(icon.bytes()).unwrap();
+        assert_eq!(image.dimensions(), (ICON_SIZE, ICON_SIZE));
+        assert_eq!(icon.extension(), "png");
+    }
+
+    fn style(mask: IconMask) -> IconStyle {
+        IconStyle {
+            background: true,
+            background_color: Some("#336699".to_owned()),
+            padding: 10,
+            mask,
+        }
+    }
+
+    #[test]
+    fn parse_color_accepts_the_color_field_formats() {
+        assert_eq!(parse_color("#fff"), Ok(Rgba([255, 255, 255, 255])));
+        assert_eq!(parse_color(" #336699 "), Ok(Rgba([0x33, 0x66, 0x99, 255])));
+        assert_eq!(parse_color("#80ff0000"), Ok(Rgba([255, 0, 0, 0x80])));
+        assert!(parse_color("red").is_err());
+        assert!(parse_color("#12345").is_err());
+        assert!(parse_color("#ggg").is_err());
+    }
+
+    #[test]
+    fn squircle_covers_the_center_but_not_the_corners() {
+        assert_eq!(squircle_coverage(ICON_SIZE / 2, ICON_SIZE / 2), 1.0);
+        assert_eq!(squircle_coverage(0, 0), 0.0);
+        assert_eq!(squircle_coverage(ICON_SIZE - 1, ICON_SIZE - 1), 0.0);
+        // The edge crosses the diagonal at about 16.6 pixels from the corner
+        let edge = squircle_coverage(16, 16);
+        assert!(edge > 0.0 && edge < 1.0, "{}", edge);
+
+        let path = squircle_path();
+        assert!(path.starts_with("M256.00,128.00 L"), "{}", path);
+        assert!(path.ends_with('Z'));
+        assert_eq!(path.matches(" L").count(), SQUIRCLE_POINTS as usize - 1);
+    }
+
+    #[test]
+    fn plain_style_keeps_the_icon() {
+        let mut warnings = Vec::new();
+        let icon = compose(
+            IconData::Svg(b"<svg/>".to_vec()),
+            &IconStyle::default(),
+            "",
+            &mut warnings,
+        );
+        assert_eq!(icon.bytes(), b"<svg/>");
+        assert!(warnings.is_empty());
+    }
+
+    #[test]
+    fn png_is_put_on_a_masked_background() {
+        let icon = process_icon(&png(32, 32), None).unwrap();
+        let mut warnings = Vec::new();
+        let icon = compose(icon, &style(IconMask::Squircle), "#ffffff", &mut warnings);
+        assert!(warnings.is_empty());
+        let image = image::load_from_memory(icon.bytes()).unwrap().to_rgba();
+        assert_eq!(image.dimensions(), (ICON_SIZE, ICON_SIZE));
+        // The icon is transparent, so the background shows through
+        assert_eq!(
+            image.get_pixel(ICON_SIZE / 2, ICON_SIZE / 2),
+            &Rgba([0x33, 0x66, 0x99, 255])
+        );
+        assert_eq!(image.get_pixel(0, 0)[3], 0);
+    }
+
+    #[test]
+    fn unknown_colors_leave_out_the_background() {
+        let icon = process_icon(&png(32, 32), None).unwrap();
+        let style = IconStyle {
+            background_color: Some("rebeccapurple".to_owned()),
+            ..style(IconMask::None)
+        };
+        let mut warnings = Vec::new();
+        let icon = compose(icon, &style, "#ffffff", &mut warnings);
+        let image = image::load_from_memory(icon.bytes()).unwrap().to_rgba();
+        assert_eq!(image.get_pixel(ICON_SIZE / 2, ICON_SIZE / 2)[3], 0);
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].starts_with("Leaving out the icon background"));
+    }
+
+    #[test]
+    fn svg_content_is_nested() {
+        let svg = br#"<?xml version="1.0"?>
+<!-- <svg> in a comment -->
+<svg xmlns="http://www.w3.org/2000/svg" xmlns:xlink="http://www.w3.org/1999/xlink"
+     viewBox="0 0 10 20" fill='#000' data-label='say "hi" > bye'><rect width="10" height="20"/></svg>"#;
+        let composed = compose_svg(
+            svg,
+            IconMask::Squircle,
+            Some(Rgba([0x33, 0x66, 0x99, 255])),
+            128,
+        )
+        .unwrap();
+        let composed = String::from_utf8(composed).unwrap();
+        assert!(composed.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\" xmlns:xlink="));
+        assert!(composed.contains("clip-path=\"url(#webber-mask)\""));
+        assert!(composed.contains("fill=\"#336699\""));
+        assert!(composed.contains(" fill=\"#000\" data-label=\"say &quot;hi&quot; > bye\">"));
+        assert!(composed.contains("<rect width=\"10\" height=\"20\"/>"));
+        // Scaled to the inner size by the longer side, and centered
+        assert!(
+            composed.contains("translate(96.000 64.000) scale(6.40000)"),
+            "{}",
+            composed
+        );
+    }
+
+    #[test]
+    fn svg_with_a_doctype_is_refused() {
+        let svg = br#"<?xml version="1.0"?>
+<!DOCTYPE svg [<!ENTITY color "red">]>
+<svg viewBox="0 0 10 10"><rect fill="&color;"/></svg>"#;
+        let err = compose_svg(svg, IconMask::None, None, 128).unwrap_err();
+        assert!(err.contains("DOCTYPE"), "{}", err);
+        assert!(compose_svg(b"<svgx/>", IconMask::None, None, 128).is_err());
+    }
+
+    #[test]
+    fn initials_of_the_first_two_words() {
+        assert_eq!(initials("Tom & Jerry").as_deref(), Some("TJ"));
+        assert_eq!(initials("hacker news weekly").as_deref(), Some("HN"));
+        assert_eq!(initials("über-eats").as_deref(), Some("ÜE"));
+        assert_eq!(initials("9gag").as_deref(), Some("9"));
+        assert_eq!(initials(" - & "), None);
+    }
+
+    #[test]
+    fn letter_avatar_uses_the_theme_color() {
+        let svg =
+            String::from_utf8(letter_avatar("Tom & Jerry", "tom.example", "#ffffff")).unwrap();
+        assert!(svg.contains("<rect width=\"256\" height=\"256\" fill=\"#ffffff\"/>"));
+        // Dark letters on a light background
+        assert!(svg.contains("fill=\"#333333\""));
+        assert!(svg.contains(">TJ</text>"));
+    }
+
+    #[test]
+    fn letter_avatar_falls_back_to_the_host() {
+        let svg = String::from_utf8(letter_avatar("", "example.com", "not a color")).unwrap();
+        assert!(svg.contains(">E</text>"));
+        assert!(svg.contains("fill=\"#ffffff\""));
+        // The same host always gets the same color
+        assert_eq!(
+            svg.as_bytes(),
+            letter_avatar("", "example.com", "").as_slice()
+        );
+        assert!(AVATAR_COLORS
+            .iter()
+            .any(|color| svg.contains(&format!("fill=\"{}\"/>", color))));
+
+        let svg = String::from_utf8(letter_avatar("", "", "")).unwrap();
+        assert!(svg.contains(">?</text>"));
+    }
+}
@@ -0,0 +1,316 @@
+use std::collections::HashMap;
+#[cfg(feature = "gui")]
+use std::path::Path;
+
+#[cfg(feature = "gui")]
+use serde::Deserialize;
+
+#[cfg(feature = "gui")]
+use crate::archive;
+#[cfg(feature = "gui")]
+use crate::click::Package;
+#[cfg(feature = "gui")]
+use crate::container::ContainerOptions;
+#[cfg(feature = "gui")]
+use crate::library;
+
+// Importing packages is only offered by the user interface
+#[cfg(feature = "gui")]
+#[derive(Deserialize, Default)]
+#[serde(default)]
+struct ClickManifest {
+    name: String,
+    title: String,
+    version: String,
+    hooks: HashMap<String, ClickHook>,
+}
+
+#[cfg(feature = "gui")]
+#[derive(Deserialize, Default)]
+#[serde(default)]
+struct ClickHook {
+    desktop: Option<String>,
+}
+
+// Turns a click package containing a webapp-container shortcut into a package definition
+#[cfg(feature = "gui")]
+pub fn import_click(bytes: &[u8]) -> Result<Package, String> {
+    let mut click_archive = ar::Archive::new(bytes);
+    let mut control = None;
+    let mut data = None;
+    while let Some(entry) = click_archive.next_entry() {
+        let mut entry = entry.map_err(|err| err.to_string())?;
+        let identifier = String::from_utf8_lossy(entry.header().identifier()).into_owned();
+        match identifier.trim_end_matches('/') {
+            "control.tar.gz" => {
+                control = Some(
+                    archive::read_tar_gz(&mut entry, |path| path == "manifest")
+                        .map_err(|err| err.to_string())?,
+                )
+            }
+            "data.tar.gz" => {
+                // Only the desktop file and the icon are of interest
+                data = Some(
+                    archive::read_tar_gz(&mut entry, |path| {
+                        path.ends_with(".desktop") || is_image_path(path)
+                    })
+                    .map_err(|err| err.to_string())?,
+                )
+            }
+            _ => {}
+        }
+    }
+    let control = control.ok_or("Not a click package: control.tar.gz is missing")?;
+    let data = data.ok_or("Not a click package: data.tar.gz is missing")?;
+
+    let manifest = control
+        .iter()
+        .find(|(path, _)| path == "manifest")
+        .ok_or("The click package has no manifest")?;
+    let manifest: ClickManifest =
+        serde_json::from_slice(&manifest.1).map_err(|err| format!("Invalid manifest: {}", err))?;
+
+    let desktop_path = manifest
+        .hooks
+        .values()
+        .find_map(|hook| hook.desktop.clone());
+    let desktop = data
+        .iter()
+        .find(|(path, _)| match &desktop_path {
+            Some(desktop_path) => path == desktop_path.trim_start_matches("./"),
+            None => path.ends_with(".desktop"),
+        })
+        .ok_or("The click package has no desktop file")?;
+    let desktop = desktop_entries(&String::from_utf8_lossy(&desktop.1));
+
+    let exec = desktop
+        .get("Exec")
+        .ok_or("The desktop file has no Exec line")?;
+    let args = split_exec(exec);
+    if !args
+        .first()
+        .map(|cmd| cmd.ends_with("webapp-container"))
+        .unwrap_or(false)
+    {
+        return Err("The click package is not a webapp-container shortcut".to_owned());
+    }
+
+    let mut url = None;
+    let mut url_patterns = String::new();
+    let mut container_options = ContainerOptions {
+        store_session_cookies: false,
+        ..Default::default()
+    };
+    for arg in args.iter().skip(1) {
+        if let Some(patterns) = arg.strip_prefix("--webappUrlPatterns=") {
+            url_patterns = patterns.to_owned();
+        } else if container_options.parse_arg(arg) {
+            continue;
+        } else if !arg.starts_with('-') && !arg.starts_with('%') {
+            url = Some(arg.clone());
+        }
+    }
+    let url = url.ok_or("The shortcut has no url")?;
+
+    let mut package = Package {
+        url,
+        name: desktop
+            .get("Name")
+            .cloned()
+            .unwrap_or_else(|| manifest.title.clone()),
+        theme_color: desktop
+            .get("X-Ubuntu-Splash-Color")
+            .cloned()
+            .unwrap_or_else(|| "#ffffff".to_owned()),
+        url_patterns,
+        version: manifest.version,
+        container_options,
+        ..Default::default()
+    };
+    // A name that was picked or made unique has to stay, or the rebuild installs as another app
+    if let Some(appname) = manifest.name.strip_suffix(".webber") {
+        if appname != package.derived_appname(false) {
+            package.package_name = Some(appname.to_owned());
+        }
+    }
+
+    if let Some(icon) = desktop.get("Icon") {
+        let icon = icon.trim_start_matches("./");
+        if let Some((_, bytes)) = data.iter().find(|(path, _)| path == icon) {
+            package.icon_url = save_icon(&package, icon, bytes)?;
+        }
+    }
+
+    Ok(package)
+}
+
+// Url a webapp-container desktop file opens
+pub fn desktop_url(content: &str) -> Option<String> {
+    let desktop = desktop_entries(content);
+    let args = split_exec(desktop.get("Exec")?);
+    if !args.first()?.ends_with("webapp-container") {
+        return None;
+    }
+    args.into_iter()
+        .skip(1)
+        .filter(|arg| !arg.starts_with('-') && !arg.starts_with('%'))
+        .last()
+}
+
+#[cfg(feature = "gui")]
+fn is_image_path(path: &str) -> bool {
+    const EXTENSIONS: &[&str] = &["png", "svg", "jpg", "jpeg", "ico", "gif", "webp"];
+    Path::new(path)
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| EXTENSIONS.contains(&ext.to_ascii_lowercase().as_str()))
+}
+
+// The icon is only inside the click, keep a copy so that the shortcut can be rebuilt
+#[cfg(feature = "gui")]
+fn save_icon(package: &Package, icon: &str, bytes: &[u8]) -> Result<String, String> {
+    let ext = Path::new(icon)
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .unwrap_or("png");
+    library::store_icon(&format!("{}.{}", package.appname(), ext), bytes)
+}
+
+fn desktop_entries(content: &str) -> HashMap<String, String> {
+    let mut entries = HashMap::new();
+    let mut in_desktop_entry = false;
+    for line in content.lines() {
+        let line = line.trim();
+        if line.starts_with('[') {
+            in_desktop_entry = line == "[Desktop Entry]";
+        } else if in_desktop_entry && !line.starts_with('#') {
+            if let Some(pos) = line.find('=') {
+                let key = line[..pos].trim().to_owned();
+                let value = line[pos + 1..].trim().to_owned();
+                entries.entry(key).or_insert(value);
+            }
+        }
+    }
+    entries
+}
+
+// Splits an Exec line into arguments, honoring the quoting rules of the desktop entry spec
+fn split_exec(exec: &str) -> Vec<String> {
+    let exec = unescape_string(exec);
+    let mut args = Vec::new();
+    let mut arg = String::new();
+    let mut in_arg = false;
+    let mut quoted = false;
+    let mut chars = exec.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' => {
+                quoted = !quoted;
+                in_arg = true;
+            }
+            '\\' if quoted => {
+                if let Some(next) = chars.next() {
+                    arg.push(next);
+                }
+            }
+            // "%%" is a literal percent sign, field codes like %u are kept as they are
+            '%' if chars.as_str().starts_with('%') => {
+                chars.next();
+                arg.push('%');
+                in_arg = true;
+            }
+            c if c.is_whitespace() && !quoted => {
+                if in_arg {
+                    args.push(arg.clone());
+                    arg.clear();
+                    in_arg = false;
+                }
+            }
+            c => {
+                arg.push(c);
+                in_arg = true;
+            }
+        }
+    }
+    if in_arg {
+        args.push(arg);
+    }
+    args
+}
+
+// Undoes the escapes of the string value type, they apply before the quoting of Exec
+fn unescape_string(value: &str) -> String {
+    let mut unescaped = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            unescaped.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('s') => unescaped.push(' '),
+            Some('n') => unescaped.push('\n'),
+            Some('t') => unescaped.push('\t'),
+            Some('r') => unescaped.push('\r'),
+            Some('\\') => unescaped.push('\\'),
+            // Not a string escape, it is left for the quoting rules
+            Some(c) => {
+                unescaped.push('\\');
+                unescaped.push(c);
+            }
+            None => unescaped.push('\\'),
+        }
+    }
+    unescaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::container::quote_exec_arg;
+
+    // The examples of the Exec key in the desktop entry spec, as they are written in the file
+    const SPEC_EXAMPLES: &[(&str, &str)] = &[
+        ("$HOME", r#""\\$HOME""#),
+        (r"C:\dir", r#""C:\\\\dir""#),
+        (r#"say "hi""#, r#""say \\"hi\\"""#),
+        ("`id`", r#""\\`id\\`""#),
+    ];
+
+    #[test]
+    fn quote_exec_arg_follows_the_spec() {
+        for (arg, quoted) in SPEC_EXAMPLES {
+            assert_eq!(quote_exec_arg(arg), *quoted);
+        }
+        assert_eq!(quote_exec_arg("a b"), r#""a b""#);
+        assert_eq!(quote_exec_arg("caf%C3%A9"), "caf%%C3%%A9");
+        assert_eq!(
+            quote_exec_arg("https://example.com/"),
+            "https://example.com/"
+        );
+    }
+
+    #[test]
+    fn split_exec_follows_the_spec() {
+        for (arg, quoted) in SPEC_EXAMPLES {
+            let exec = format!("webapp-container {} %u", quoted);
+            assert_eq!(split_exec(&exec), vec!["webapp-container", *arg, "%u"]);
+        }
+    }
+
+    #[test]
+    fn split_exec_applies_string_escapes_first() {
+        assert_eq!(
+            split_exec(r#"webapp-container "a\sb" "c\td""#),
+            vec!["webapp-container", "a b", "c\td"]
+        );
+    }
+
+    #[test]
+    fn split_exec_keeps_field_codes() {
+        assert_eq!(
+            split_exec("webapp-container 100%% %u"),
+            vec!["webapp-container", "100%", "%u"]
+        );
+    }
+}
@@ -0,0 +1,156 @@
+use std::collections::HashSet;
+use std::fs;
+use std::path::PathBuf;
+
+#[cfg(feature = "gui")]
+use url::Url;
+
+use crate::click::{self, Package};
+use crate::import;
+
+fn library_path() -> Result<PathBuf, String> {
+    xdg::BaseDirectories::with_prefix("webber.timsueberkrueb")
+        .map_err(|err| err.to_string())?
+        .place_data_file("library.json")
+        .map_err(|err| err.to_string())
+}
+
+// Where picked and imported icons are kept, the only local files a package may refer to
+pub fn icons_dir() -> Result<PathBuf, String> {
+    let dirs = xdg::BaseDirectories::with_prefix("webber.timsueberkrueb")
+        .map_err(|err| err.to_string())?;
+    Ok(dirs.get_data_home().join("icons"))
+}
+
+// Keeps an icon that only exists locally or temporarily, returns its url
+#[cfg(feature = "gui")]
+pub fn store_icon(filename: &str, bytes: &[u8]) -> Result<String, String> {
+    let dir = icons_dir()?;
+    fs::create_dir_all(&dir).map_err(|err| err.to_string())?;
+    let path = dir.join(filename);
+    fs::write(&path, bytes).map_err(|err| err.to_string())?;
+    Url::from_file_path(&path)
+        .map(|url| url.to_string())
+        .map_err(|_| format!("Invalid icon path: {}", path.display()))
+}
+
+pub fn load() -> Result<Vec<Package>, String> {
+    let path = library_path()?;
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let content = fs::read_to_string(&path).map_err(|err| err.to_string())?;
+    serde_json::from_str(&content).map_err(|err| err.to_string())
+}
+
+pub fn save(packages: &[Package]) -> Result<(), String> {
+    let path = library_path()?;
+    let content = serde_json::to_string_pretty(packages).map_err(|err| err.to_string())?;
+    // Write to a temporary file first so that a crash can't leave a truncated library behind
+    let tmp_path = path.with_extension("json.tmp");
+    fs::write(&tmp_path, content).map_err(|err| err.to_string())?;
+    fs::rename(&tmp_path, &path).map_err(|err| err.to_string())
+}
+
+// Adds the package, replacing an earlier build of the same app
+pub fn add(package: Package) -> Result<(), String> {
+    let mut packages = load()?;
+    let appname = package.appname();
+    match packages.iter().position(|p| p.appname() == appname) {
+        Some(idx) => packages[idx] = package,
+        None => packages.push(package),
+    }
+    save(&packages)
+}
+
+// Version the next build of `appname` should get, so that the installer accepts it as upgrade
+pub fn next_version(appname: &str) -> Result<String, String> {
+    let version = load()?
+        .into_iter()
+        .find(|p| p.appname() == appname)
+        .map(|p| click::bump_version(&p.version))
+        .unwrap_or_else(|| click::INITIAL_VERSION.to_owned());
+    Ok(version)
+}
+
+#[cfg(feature = "gui")]
+pub fn remove(appname: &str) -> Result<(), String> {
+    let mut packages = load()?;
+    packages.retain(|p| p.appname() != appname);
+    save(&packages)
+}
+
+// Shortcuts installed on the device, found through the desktop files click creates for them.
+// They are named <package>_<app>_<version>.desktop, our packages are called <appname>.webber
+fn installed_packages() -> Vec<Package> {
+    let dir = match xdg::BaseDirectories::new() {
+        Ok(dirs) => dirs.get_data_home().join("applications"),
+        Err(_) => return Vec::new(),
+    };
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(_) => return Vec::new(),
+    };
+    entries
+        .filter_map(|entry| {
+            let entry = entry.ok()?;
+            let filename = entry.file_name().into_string().ok()?;
+            if !filename.ends_with(".desktop") {
+                return None;
+            }
+            let pos = filename.find(".webber_")?;
+            let content = fs::read_to_string(entry.path()).ok()?;
+            Some(Package {
+                url: import::desktop_url(&content)?,
+                package_name: Some(filename[..pos].to_owned()),
+                ..Default::default()
+            })
+        })
+        .collect()
+}
+
+// Names taken by shortcuts for a different url than `package`
+fn taken_appnames(package: &Package) -> HashSet<String> {
+    load()
+        .unwrap_or_default()
+        .into_iter()
+        .chain(installed_packages())
+        .filter(|other| !other.same_target(package))
+        .map(|other| other.appname())
+        .collect()
+}
+
+// Url of another shortcut that already uses the name of `package`
+pub fn clash(package: &Package) -> Option<String> {
+    let appname = package.appname();
+    load()
+        .unwrap_or_default()
+        .into_iter()
+        .chain(installed_packages())
+        .find(|other| other.appname() == appname && !other.same_target(package))
+        .map(|other| other.url)
+}
+
+// Gives the package a name of its own if the one derived from the host is taken, unless
+// the user picked one
+pub fn resolve_appname(package: &mut Package) {
+    if package
+        .package_name
+        .as_ref()
+        .is_some_and(|name| !name.trim().is_empty())
+    {
+        return;
+    }
+    let taken = taken_appnames(package);
+    if !taken.contains(&package.appname()) {
+        return;
+    }
+    let base = package.derived_appname(true);
+    let mut appname = base.clone();
+    let mut n = 2;
+    while taken.contains(&appname) {
+        appname = format!("{}-{}", base, n);
+        n += 1;
+    }
+    package.package_name = Some(appname);
+}
@@ -1,18 +1,50 @@
+#[cfg(feature = "gui")]
 #[macro_use]
 extern crate cstr;
+#[cfg(feature = "gui")]
 #[macro_use]
 extern crate cpp;
+#[cfg(feature = "gui")]
 #[macro_use]
 extern crate qmetaobject;
 
+#[cfg(feature = "gui")]
 use qmetaobject::*;
 
+mod archive;
+mod cache;
+mod cli;
 mod click;
+mod container;
 mod core;
+mod icons;
+mod imaging;
+mod import;
+mod library;
+#[cfg(feature = "gui")]
 mod model;
+mod net;
+mod patterns;
+#[cfg(feature = "gui")]
 mod qrc;
+mod webmanifest;
 
 fn main() {
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+    if cli::is_command(&args) {
+        std::process::exit(cli::run(&args));
+    }
+    run_gui();
+}
+
+// Built without the user interface, all there is to show is the usage
+#[cfg(not(feature = "gui"))]
+fn run_gui() {
+    std::process::exit(cli::run(&[]));
+}
+
+#[cfg(feature = "gui")]
+fn run_gui() {
     unsafe {
         cpp! { {
             #include <QtCore/QCoreApplication>
@@ -26,6 +58,7 @@ fn main() {
     qrc::load();
     qml_register_type::<model::WebScraper>(cstr!("Webber"), 1, 0, cstr!("WebScraper"));
     qml_register_type::<model::AppModel>(cstr!("Webber"), 1, 0, cstr!("AppModel"));
+    qml_register_type::<model::LibraryModel>(cstr!("Webber"), 1, 0, cstr!("LibraryModel"));
     qml_register_type::<model::UrlPatternsModel>(cstr!("Webber"), 1, 0, cstr!("UrlPatternsModel"));
     let mut engine = QmlEngine::new();
     engine.load_file("qrc:/qml/Main.qml".into());
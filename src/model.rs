@@ -3,7 +3,13 @@ use std::collections::HashMap;
 use qmetaobject::*;
 
 use crate::click;
+use crate::container::{ContainerOptions, UserAgent};
 use crate::core;
+use crate::imaging::{self, IconData, IconMask, IconStyle};
+use crate::import;
+use crate::library;
+use crate::net;
+use crate::patterns;
 
 #[allow(non_snake_case)]
 #[derive(QObject, Default)]
@@ -12,47 +18,82 @@ pub struct WebScraper {
     url: qt_property!(QString; NOTIFY urlChanged),
     urlChanged: qt_signal!(),
     siteName: qt_property!(QString; NOTIFY scraped),
+    shortName: qt_property!(QString; NOTIFY scraped),
     title: qt_property!(QString; NOTIFY scraped),
     themeColor: qt_property!(QString; NOTIFY scraped),
+    backgroundColor: qt_property!(QString; NOTIFY scraped),
+    startUrl: qt_property!(QString; NOTIFY scraped),
+    scope: qt_property!(QString; NOTIFY scraped),
     iconUrl: qt_property!(QString; NOTIFY scraped),
     defaultUrlPatterns: qt_property!(QVariant; NOTIFY scraped),
+    suggestedUrlPatterns: qt_property!(QVariant; NOTIFY scraped),
+    suggestedUrlPatternReasons: qt_property!(QVariant; NOTIFY scraped),
+    redirectChain: qt_property!(QVariant; NOTIFY scraped),
+    // Icon urls declared in the web app manifest
+    manifestIcons: qt_property!(QVariant; NOTIFY scraped),
+    // Where the redirects of the entered url ended
+    finalUrl: qt_property!(QString; NOTIFY scraped),
+    // The page's <link rel="canonical">, empty if it has none
+    canonicalUrl: qt_property!(QString; NOTIFY scraped),
+    // Better launch url than the one entered, e.g. after redirects, empty if there is none
+    resolvedUrl: qt_property!(QString; NOTIFY scraped),
+    // Plain http url the site had to be loaded from, empty if it supports https
+    fallbackUrl: qt_property!(QString; NOTIFY fallbackUrlChanged),
+    fallbackUrlChanged: qt_signal!(),
+    // Problems that did not stop the scrape
+    warnings: qt_property!(QVariant; NOTIFY scraped),
     scraped: qt_signal!(),
     busy: qt_property!(bool; NOTIFY busyChanged),
     busyChanged: qt_signal!(),
     errorString: qt_property!(QString; NOTIFY errorStringChanged),
+    errorKind: qt_property!(QString; NOTIFY errorStringChanged),
     errorStringChanged: qt_signal!(),
+    userAgentPreset: qt_property!(QString; NOTIFY userAgentChanged),
+    userAgent: qt_property!(QString; NOTIFY userAgentChanged),
+    userAgentChanged: qt_signal!(),
+    progress: qt_property!(f64; NOTIFY progressChanged),
+    progressChanged: qt_signal!(),
     scrape: qt_method!(fn(&mut self)),
-    scrape_mutex: std::sync::Arc<std::sync::Mutex<()>>,
+    tasks: net::Tasks,
 }
 
 impl WebScraper {
     fn scrape(&mut self) {
         let url = self.url.to_string();
 
+        // Only a scheme the user left out may be replaced with http
+        let http_fallback = !core::has_scheme(&url);
         match core::validate_url(url) {
             Ok(url) => {
-                self.errorString = QString::default();
-                self.errorStringChanged();
-                self.run_scrape_thread(url);
+                self.set_error(String::default(), String::default());
+                self.fallbackUrl = QString::default();
+                self.fallbackUrlChanged();
+                self.run_scrape_thread(url, http_fallback);
             }
             Err(s) => {
                 let msg = format!("Invalid url: {}", s);
-                let msg = QString::from(msg);
-                self.errorString = msg;
-                self.errorStringChanged();
+                self.set_error("invalid_url".to_owned(), msg);
             }
         }
     }
 
-    fn run_scrape_thread(&mut self, url: url::Url) {
+    fn set_error(&mut self, kind: String, msg: String) {
+        self.errorKind = QString::from(kind);
+        self.errorString = QString::from(msg);
+        self.errorStringChanged();
+    }
+
+    fn run_scrape_thread(&mut self, url: url::Url, http_fallback: bool) {
+        let user_agent =
+            UserAgent::from_preset(&self.userAgentPreset.to_string(), &self.userAgent.to_string());
+
         self.busy = true;
         self.busyChanged();
 
         let qptr = QPointer::from(&*self);
-        let set_error_string = qmetaobject::queued_callback(move |val: QString| {
+        let set_error = qmetaobject::queued_callback(move |(kind, msg): (String, String)| {
             if let Some(self_) = qptr.as_pinned() {
-                self_.borrow_mut().errorString = val;
-                self_.borrow().errorStringChanged();
+                self_.borrow_mut().set_error(kind, msg);
             }
         });
         let qptr = QPointer::from(&*self);
@@ -60,13 +101,45 @@ impl WebScraper {
             if let Some(self_) = qptr.as_pinned() {
                 self_.borrow_mut().title = QString::from(res.title);
                 self_.borrow_mut().siteName = QString::from(res.site_name);
+                self_.borrow_mut().shortName = QString::from(res.short_name);
                 self_.borrow_mut().themeColor = QString::from(res.theme_color);
+                self_.borrow_mut().backgroundColor = QString::from(res.background_color);
+                self_.borrow_mut().startUrl = QString::from(res.start_url);
+                self_.borrow_mut().scope = QString::from(res.scope);
                 self_.borrow_mut().iconUrl = QString::from(res.icon_url);
                 let mut list = QVariantList::default();
                 for pat in res.default_url_patterns {
                     list.push(QVariant::from(QString::from(pat)));
                 }
                 self_.borrow_mut().defaultUrlPatterns = QVariant::from(list);
+                let mut patterns = QVariantList::default();
+                let mut reasons = QVariantList::default();
+                for suggestion in res.suggested_url_patterns {
+                    patterns.push(QVariant::from(QString::from(suggestion.pattern)));
+                    reasons.push(QVariant::from(QString::from(suggestion.reason)));
+                }
+                self_.borrow_mut().suggestedUrlPatterns = QVariant::from(patterns);
+                self_.borrow_mut().suggestedUrlPatternReasons = QVariant::from(reasons);
+                let mut chain = QVariantList::default();
+                for url in res.redirect_chain {
+                    chain.push(QVariant::from(QString::from(url)));
+                }
+                self_.borrow_mut().redirectChain = QVariant::from(chain);
+                let mut icons = QVariantList::default();
+                for icon in res.icons {
+                    icons.push(QVariant::from(QString::from(icon.src)));
+                }
+                self_.borrow_mut().manifestIcons = QVariant::from(icons);
+                self_.borrow_mut().finalUrl = QString::from(res.final_url);
+                self_.borrow_mut().canonicalUrl = QString::from(res.canonical_url);
+                self_.borrow_mut().resolvedUrl = QString::from(res.resolved_url);
+                self_.borrow_mut().fallbackUrl = QString::from(res.fallback_url);
+                let mut warnings = QVariantList::default();
+                for warning in res.warnings {
+                    warnings.push(QVariant::from(QString::from(warning)));
+                }
+                self_.borrow_mut().warnings = QVariant::from(warnings);
+                self_.borrow().fallbackUrlChanged();
                 self_.borrow().scraped();
             }
         });
@@ -78,33 +151,260 @@ impl WebScraper {
             }
         });
 
-        let mutex = self.scrape_mutex.clone();
+        // Starting a new task cancels the scrape of an outdated url
+        let task = self.tasks.start(progress_callback(self));
+        self.progress = 0.0;
+        self.progressChanged();
 
         std::thread::spawn(move || {
-            if let Ok(lock) = mutex.try_lock() {
-                match core::scrape_url(url) {
-                    Ok(res) => set_scrape_result(res),
-                    Err(err) => {
-                        let msg = format!("Failed to load site: {}", err);
-                        set_error_string(QString::from(msg));
-                    }
-                };
-                drop(lock)
+            let res = core::scrape_url(url, &user_agent, &task, http_fallback);
+            // A newer scrape is running and owns the busy state now
+            if task.is_cancelled() {
+                return;
             }
+            match res {
+                Ok(res) => set_scrape_result(res),
+                Err(err) => {
+                    let msg = format!("Failed to load site: {}", err);
+                    set_error((err.kind().to_owned(), msg));
+                }
+            };
             set_busy(false);
         });
     }
 }
 
+// Updates the progress property of `object`, which is -1 while the size is unknown
+fn progress_callback<T>(object: &T) -> impl Fn(f64) + Send + Sync
+where
+    T: QObject + HasProgress + 'static,
+{
+    let qptr = QPointer::from(object);
+    let set_progress = qmetaobject::queued_callback(move |progress: f64| {
+        if let Some(self_) = qptr.as_pinned() {
+            self_.borrow_mut().set_progress(progress);
+        }
+    });
+    let set_progress = std::sync::Mutex::new(set_progress);
+    move |progress| {
+        if let Ok(set_progress) = set_progress.lock() {
+            set_progress(progress);
+        }
+    }
+}
+
+trait HasProgress {
+    fn set_progress(&mut self, progress: f64);
+}
+
+impl HasProgress for WebScraper {
+    fn set_progress(&mut self, progress: f64) {
+        self.progress = progress;
+        self.progressChanged();
+    }
+}
+
+impl HasProgress for AppModel {
+    fn set_progress(&mut self, progress: f64) {
+        self.progress = progress;
+        self.progressChanged();
+    }
+}
+
 #[allow(non_snake_case)]
 #[derive(QObject, Default)]
 pub struct AppModel {
     base: qt_base_class!(trait QObject),
     create: qt_method!(fn(&mut self, url: String, name: String, theme_color: String, icon_url: String, url_patterns: String)),
     created: qt_signal!(),
+    failed: qt_signal!(error: QString),
+    errorString: qt_property!(QString; NOTIFY errorStringChanged),
+    errorStringChanged: qt_signal!(),
+    // Problems that did not stop the last package from being created
+    warningString: qt_property!(QString; NOTIFY created),
+    storeSessionCookies: qt_property!(bool; NOTIFY optionsChanged),
+    fullscreen: qt_property!(bool; NOTIFY optionsChanged),
+    enableBackForward: qt_property!(bool; NOTIFY optionsChanged),
+    enableAddressbar: qt_property!(bool; NOTIFY optionsChanged),
+    localContentCanAccessRemoteUrls: qt_property!(bool; NOTIFY optionsChanged),
+    openExternalUrlInOverlay: qt_property!(bool; NOTIFY optionsChanged),
+    enableMediaHubAudio: qt_property!(bool; NOTIFY optionsChanged),
+    userAgentPreset: qt_property!(QString; NOTIFY optionsChanged),
+    userAgent: qt_property!(QString; NOTIFY optionsChanged),
+    webappModelSearchPath: qt_property!(QString; NOTIFY optionsChanged),
+    optionsChanged: qt_signal!(),
+    packageName: qt_property!(QString; NOTIFY packageNameChanged),
+    packageNameChanged: qt_signal!(),
+    resolvePackageName: qt_method!(fn(&self, url: String, package_name: String) -> QString),
+    packageNameClash: qt_method!(fn(&self, url: String, package_name: String) -> QString),
+    iconBackground: qt_property!(bool; NOTIFY iconStyleChanged),
+    iconBackgroundColor: qt_property!(QString; NOTIFY iconStyleChanged),
+    iconPadding: qt_property!(u32; NOTIFY iconStyleChanged),
+    iconMask: qt_property!(QString; NOTIFY iconStyleChanged),
+    // The icon url was picked by the user rather than scraped
+    customIcon: qt_property!(bool; NOTIFY iconStyleChanged),
+    iconStyleChanged: qt_signal!(),
+    iconPreview: qt_property!(QString; NOTIFY iconPreviewChanged),
+    // What the preview, and so the package, had to leave out
+    iconWarning: qt_property!(QString; NOTIFY iconPreviewChanged),
+    iconPreviewChanged: qt_signal!(),
+    updateIconPreview: qt_method!(fn(&mut self, url: String, name: String, icon_url: String, theme_color: String)),
+    importIcon: qt_method!(fn(&mut self, url: String)),
+    iconImported: qt_signal!(iconUrl: QString),
+    iconImportFailed: qt_signal!(error: QString),
+    progress: qt_property!(f64; NOTIFY progressChanged),
+    progressChanged: qt_signal!(),
+    tasks: net::Tasks,
+    preview_tasks: net::Tasks,
+    import_tasks: net::Tasks,
 }
 
 impl AppModel {
+    fn container_options(&self) -> ContainerOptions {
+        let non_empty = |s: &QString| Some(s.to_string()).filter(|s| !s.trim().is_empty());
+        ContainerOptions {
+            store_session_cookies: self.storeSessionCookies,
+            fullscreen: self.fullscreen,
+            enable_back_forward: self.enableBackForward,
+            enable_addressbar: self.enableAddressbar,
+            local_content_can_access_remote_urls: self.localContentCanAccessRemoteUrls,
+            open_external_url_in_overlay: self.openExternalUrlInOverlay,
+            enable_media_hub_audio: self.enableMediaHubAudio,
+            user_agent: UserAgent::from_preset(
+                &self.userAgentPreset.to_string(),
+                &self.userAgent.to_string(),
+            ),
+            webapp_model_search_path: non_empty(&self.webappModelSearchPath),
+        }
+    }
+
+    fn icon_style(&self) -> IconStyle {
+        let color = self.iconBackgroundColor.to_string();
+        IconStyle {
+            background: self.iconBackground,
+            background_color: Some(color).filter(|color| !color.trim().is_empty()),
+            padding: self.iconPadding,
+            mask: IconMask::from_name(&self.iconMask.to_string()),
+        }
+    }
+
+    // Renders the icon like it will be packaged, only the most recent request is shown
+    #[allow(non_snake_case)]
+    fn updateIconPreview(
+        &mut self,
+        url: String,
+        name: String,
+        icon_url: String,
+        theme_color: String,
+    ) {
+        let task = self.preview_tasks.start(|_| {});
+
+        let qptr = QPointer::from(&*self);
+        let current = task.clone();
+        let set_preview = qmetaobject::queued_callback(move |(preview, warning): (String, String)| {
+            if let Some(self_) = qptr.as_pinned() {
+                if !current.is_cancelled() {
+                    self_.borrow_mut().iconPreview = QString::from(preview);
+                    self_.borrow_mut().iconWarning = QString::from(warning);
+                    self_.borrow().iconPreviewChanged();
+                }
+            }
+        });
+
+        let package = click::Package {
+            url,
+            name,
+            icon_url,
+            theme_color,
+            icon_style: self.icon_style(),
+            custom_icon: self.customIcon,
+            ..Default::default()
+        };
+        std::thread::spawn(move || {
+            let mut warnings = Vec::new();
+            let preview = click::render_icon(&package, &task, &mut warnings)
+                .map_err(|err| err.to_string())
+                .and_then(|icon| write_icon_preview(task.id(), &icon));
+            if task.is_cancelled() {
+                return;
+            }
+            match preview {
+                Ok(preview) => set_preview((preview, warnings.join("\n"))),
+                Err(err) => {
+                    // Show the plain icon instead
+                    let warning = format!("Failed to render icon preview: {}", err);
+                    set_preview((package.icon_url, warning));
+                }
+            }
+        });
+    }
+
+    // Copies a picked image to the app's data, files handed over by the content hub are temporary.
+    // Decoding and scaling a photo takes a while, so that happens in the background.
+    #[allow(non_snake_case)]
+    fn importIcon(&mut self, url: String) {
+        let task = self.import_tasks.start(|_| {});
+
+        let qptr = QPointer::from(&*self);
+        let set_imported = qmetaobject::queued_callback(move |icon: Result<String, String>| {
+            if let Some(self_) = qptr.as_pinned() {
+                match icon {
+                    Ok(icon_url) => self_.borrow().iconImported(QString::from(icon_url)),
+                    Err(err) => self_.borrow().iconImportFailed(QString::from(err)),
+                }
+            }
+        });
+
+        let path = match url::Url::parse(&url).map(|url| url.to_file_path()) {
+            Ok(Ok(path)) => path,
+            _ => std::path::PathBuf::from(url),
+        };
+        // Read right away, the transfer is finalized and the file removed once this returns
+        let bytes = std::fs::read(&path);
+        std::thread::spawn(move || {
+            let icon = bytes
+                .map_err(|err| err.to_string())
+                .and_then(|bytes| imaging::process_icon(&bytes, None))
+                .and_then(|icon| {
+                    let digest = md5::compute(icon.bytes());
+                    let filename = format!("custom-{:x}.{}", digest, icon.extension());
+                    library::store_icon(&filename, icon.bytes())
+                });
+            // Only the most recently picked image counts
+            if task.is_cancelled() {
+                return;
+            }
+            set_imported(icon);
+        });
+    }
+
+    fn package_for(url: String, package_name: String) -> click::Package {
+        // Bare host names are completed like for scraping, anything else is kept as is
+        let url = core::validate_url(url.clone())
+            .map(|url| url.to_string())
+            .unwrap_or(url);
+        click::Package {
+            url,
+            package_name: Some(package_name).filter(|name| !name.trim().is_empty()),
+            ..Default::default()
+        }
+    }
+
+    // Name the shortcut for `url` will be installed under
+    #[allow(non_snake_case)]
+    fn resolvePackageName(&self, url: String, package_name: String) -> QString {
+        let mut package = Self::package_for(url, package_name);
+        library::resolve_appname(&mut package);
+        QString::from(package.appname())
+    }
+
+    // Url of another shortcut using the chosen name, empty if there is none
+    #[allow(non_snake_case)]
+    fn packageNameClash(&self, url: String, package_name: String) -> QString {
+        let package = Self::package_for(url, package_name);
+        QString::from(library::clash(&package).unwrap_or_default())
+    }
+
     fn create(
         &mut self,
         url: String,
@@ -113,39 +413,282 @@ impl AppModel {
         icon_url: String,
         url_patterns: String,
     ) {
-        let package = click::Package {
-            url,
+        let mut package = click::Package {
             name,
             icon_url,
             theme_color,
             url_patterns,
+            container_options: self.container_options(),
+            icon_style: self.icon_style(),
+            custom_icon: self.customIcon,
+            ..Self::package_for(url, self.packageName.to_string())
         };
 
+        self.errorString = QString::default();
+        self.errorStringChanged();
+
         let qptr = QPointer::from(&*self);
-        let set_created = qmetaobject::queued_callback(move |_| {
+        let set_created = qmetaobject::queued_callback(move |warnings: Vec<String>| {
             if let Some(self_) = qptr.as_pinned() {
+                self_.borrow_mut().warningString = QString::from(warnings.join("\n"));
                 self_.borrow().created();
             }
         });
+        let qptr = QPointer::from(&*self);
+        let set_failed = qmetaobject::queued_callback(move |val: QString| {
+            if let Some(self_) = qptr.as_pinned() {
+                self_.borrow_mut().errorString = val.clone();
+                self_.borrow().errorStringChanged();
+                self_.borrow().failed(val);
+            }
+        });
+
+        let task = self.tasks.start(progress_callback(self));
+        self.progress = 0.0;
+        self.progressChanged();
 
         std::thread::spawn(move || {
-            click::create_package(package).unwrap();
-            set_created(());
+            library::resolve_appname(&mut package);
+            if let Some(other) = library::clash(&package) {
+                set_failed(QString::from(format!(
+                    "The package name {} is already used by the shortcut for {}",
+                    package.appname(),
+                    other
+                )));
+                return;
+            }
+            // Rebuilding an existing shortcut needs a higher version to be installable
+            match library::next_version(&package.appname()) {
+                Ok(version) => package.version = version,
+                Err(err) => {
+                    set_failed(QString::from(format!("Failed to load library: {}", err)));
+                    return;
+                }
+            }
+            let mut warnings = Vec::new();
+            match click::create_package(package.clone(), &task, &mut warnings) {
+                Ok(_) => {
+                    if let Err(err) = library::add(package) {
+                        warnings.push(format!("Failed to save shortcut to library: {}", err));
+                    }
+                    set_created(warnings);
+                }
+                Err(err) => set_failed(QString::from(err.to_string())),
+            }
         });
     }
 }
 
+// Every preview gets a new file name, the QML image cache would show a stale one otherwise
+fn write_icon_preview(generation: usize, icon: &IconData) -> Result<String, String> {
+    let dir = xdg::BaseDirectories::with_prefix("webber.timsueberkrueb")
+        .map_err(|err| err.to_string())?
+        .create_cache_directory("icon-preview")
+        .map_err(|err| err.to_string())?;
+    // Older previews are not needed anymore, newer ones may still be shown
+    if let Ok(entries) = std::fs::read_dir(&dir) {
+        for entry in entries.filter_map(Result::ok) {
+            let path = entry.path();
+            let older = path
+                .file_stem()
+                .and_then(|stem| stem.to_str())
+                .and_then(|stem| stem.parse::<usize>().ok())
+                .is_some_and(|n| n < generation);
+            if older {
+                let _ = std::fs::remove_file(path);
+            }
+        }
+    }
+    let path = dir.join(format!("{}.{}", generation, icon.extension()));
+    std::fs::write(&path, icon.bytes()).map_err(|err| err.to_string())?;
+    url::Url::from_file_path(&path)
+        .map(|url| url.to_string())
+        .map_err(|_| format!("Invalid preview path: {}", path.display()))
+}
+
+const NAME_ROLE: i32 = USER_ROLE;
+const URL_ROLE: i32 = USER_ROLE + 1;
+const THEME_COLOR_ROLE: i32 = USER_ROLE + 2;
+const ICON_URL_ROLE: i32 = USER_ROLE + 3;
+const URL_PATTERNS_ROLE: i32 = USER_ROLE + 4;
+const APPNAME_ROLE: i32 = USER_ROLE + 5;
+const VERSION_ROLE: i32 = USER_ROLE + 6;
+const NEXT_VERSION_ROLE: i32 = USER_ROLE + 7;
+const CONTAINER_OPTIONS_ROLE: i32 = USER_ROLE + 8;
+const PACKAGE_NAME_ROLE: i32 = USER_ROLE + 9;
+const ICON_STYLE_ROLE: i32 = USER_ROLE + 10;
+const CUSTOM_ICON_ROLE: i32 = USER_ROLE + 11;
+
+#[allow(non_snake_case)]
+#[derive(Default, QObject)]
+pub struct LibraryModel {
+    base: qt_base_class!(trait QAbstractListModel),
+    count: qt_property!(i32; READ row_count NOTIFY count_changed),
+    count_changed: qt_signal!(),
+    errorString: qt_property!(QString; NOTIFY errorStringChanged),
+    errorStringChanged: qt_signal!(),
+    list: Vec<click::Package>,
+
+    reload: qt_method!(fn(&mut self)),
+    remove: qt_method!(fn(&mut self, row: usize) -> bool),
+    importClick: qt_method!(fn(&mut self, url: String)),
+}
+
+impl LibraryModel {
+    fn set_error_string(&mut self, error: String) {
+        self.errorString = QString::from(error);
+        self.errorStringChanged();
+    }
+
+    fn reload(&mut self) {
+        let packages = match library::load() {
+            Ok(packages) => {
+                self.set_error_string(String::default());
+                packages
+            }
+            Err(err) => {
+                self.set_error_string(format!("Failed to load library: {}", err));
+                Vec::default()
+            }
+        };
+        (self as &mut dyn QAbstractListModel).begin_reset_model();
+        self.list = packages;
+        (self as &mut dyn QAbstractListModel).end_reset_model();
+        self.count_changed();
+    }
+
+    #[allow(non_snake_case)]
+    fn importClick(&mut self, url: String) {
+        let qptr = QPointer::from(&*self);
+        let set_imported =
+            qmetaobject::queued_callback(move |package: Result<click::Package, String>| {
+                if let Some(self_) = qptr.as_pinned() {
+                    let mut self_ = self_.borrow_mut();
+                    match package.and_then(|package| {
+                        library::add(package)
+                            .map_err(|err| format!("Failed to save imported shortcut: {}", err))
+                    }) {
+                        Ok(()) => self_.reload(),
+                        Err(err) => self_.set_error_string(err),
+                    }
+                }
+            });
+
+        let path = match url::Url::parse(&url).map(|url| url.to_file_path()) {
+            Ok(Ok(path)) => path,
+            _ => std::path::PathBuf::from(url),
+        };
+        // Read right away, the transfer is finalized and the file removed once this returns
+        let bytes = std::fs::read(&path);
+        std::thread::spawn(move || {
+            let package = bytes
+                .map_err(|err| err.to_string())
+                .and_then(|bytes| import::import_click(&bytes))
+                .map_err(|err| format!("Failed to import {}: {}", path.display(), err));
+            set_imported(package);
+        });
+    }
+
+    fn remove(&mut self, row: usize) -> bool {
+        if row >= self.list.len() {
+            return false;
+        }
+        if let Err(err) = library::remove(&self.list[row].appname()) {
+            self.set_error_string(format!("Failed to remove shortcut: {}", err));
+            return false;
+        }
+        (self as &mut dyn QAbstractListModel).begin_remove_rows(row as i32, row as i32);
+        self.list.remove(row);
+        (self as &mut dyn QAbstractListModel).end_remove_rows();
+        self.count_changed();
+        true
+    }
+}
+
+impl QAbstractListModel for LibraryModel {
+    fn row_count(&self) -> i32 {
+        self.list.len() as i32
+    }
+
+    fn data(&self, index: QModelIndex, role: i32) -> QVariant {
+        let idx = index.row() as usize;
+        if let Some(package) = self.list.get(idx) {
+            let value = match role {
+                NAME_ROLE => package.name.clone(),
+                URL_ROLE => package.url.clone(),
+                THEME_COLOR_ROLE => package.theme_color.clone(),
+                ICON_URL_ROLE => package.icon_url.clone(),
+                URL_PATTERNS_ROLE => package.url_patterns.clone(),
+                APPNAME_ROLE => package.appname(),
+                VERSION_ROLE => package.version.clone(),
+                NEXT_VERSION_ROLE => click::bump_version(&package.version),
+                CONTAINER_OPTIONS_ROLE => {
+                    serde_json::to_string(&package.container_options).unwrap_or_default()
+                }
+                PACKAGE_NAME_ROLE => package.package_name.clone().unwrap_or_default(),
+                ICON_STYLE_ROLE => serde_json::to_string(&package.icon_style).unwrap_or_default(),
+                CUSTOM_ICON_ROLE => return package.custom_icon.into(),
+                _ => return QVariant::default(),
+            };
+            QString::from(value).into()
+        } else {
+            QVariant::default()
+        }
+    }
+
+    fn role_names(&self) -> HashMap<i32, QByteArray> {
+        let mut map = HashMap::new();
+        map.insert(NAME_ROLE, "name".into());
+        map.insert(URL_ROLE, "url".into());
+        map.insert(THEME_COLOR_ROLE, "themeColor".into());
+        map.insert(ICON_URL_ROLE, "iconUrl".into());
+        map.insert(URL_PATTERNS_ROLE, "urlPatterns".into());
+        map.insert(APPNAME_ROLE, "appname".into());
+        map.insert(VERSION_ROLE, "version".into());
+        map.insert(NEXT_VERSION_ROLE, "nextVersion".into());
+        map.insert(CONTAINER_OPTIONS_ROLE, "containerOptions".into());
+        map.insert(PACKAGE_NAME_ROLE, "packageName".into());
+        map.insert(ICON_STYLE_ROLE, "iconStyle".into());
+        map.insert(CUSTOM_ICON_ROLE, "customIcon".into());
+        map
+    }
+}
+
 #[derive(Default, Clone)]
 struct UrlPattern {
     url: String,
+    error: String,
+}
+
+impl UrlPattern {
+    fn new(url: &str) -> Self {
+        let url = patterns::normalize(url);
+        // Empty rows are still being edited and are left out of the patterns string
+        let error = if url.is_empty() {
+            String::default()
+        } else {
+            patterns::validate(&url).err().unwrap_or_default()
+        };
+        Self { url, error }
+    }
+
+    fn is_valid(&self) -> bool {
+        self.error.is_empty()
+    }
 }
 
+const PATTERN_URL_ROLE: i32 = USER_ROLE;
+const PATTERN_VALID_ROLE: i32 = USER_ROLE + 1;
+const PATTERN_ERROR_ROLE: i32 = USER_ROLE + 2;
+
 #[allow(non_snake_case)]
 #[derive(Default, QObject)]
 pub struct UrlPatternsModel {
     base: qt_base_class!(trait QAbstractListModel),
     count: qt_property!(i32; READ row_count NOTIFY count_changed),
     count_changed: qt_signal!(),
+    valid: qt_property!(bool; READ is_valid NOTIFY valid_changed),
+    valid_changed: qt_signal!(),
     list: Vec<UrlPattern>,
 
     setUrl: qt_method!(fn(&mut self, idx: usize, url: String) -> bool),
@@ -155,26 +698,33 @@ pub struct UrlPatternsModel {
     remove: qt_method!(fn(&mut self, index: u64) -> bool),
     clear: qt_method!(fn(&mut self)),
     getPatternsString: qt_method!(fn(&mut self) -> QString),
+    testUrl: qt_method!(fn(&self, url: String) -> i32),
 }
 
 impl UrlPatternsModel {
     #[allow(non_snake_case)]
     fn setUrl(&mut self, idx: usize, url: String) -> bool {
-        if idx > self.list.len() {
+        if idx >= self.list.len() {
             return false;
         }
-        self.list[idx].url = url;
+        self.list[idx] = UrlPattern::new(&url);
         let idx = (self as &mut dyn QAbstractListModel).row_index(idx as i32);
         (self as &mut dyn QAbstractListModel).data_changed(idx, idx);
+        self.valid_changed();
         true
     }
 
     fn add(&mut self, url: String) {
         let end = self.list.len();
         (self as &mut dyn QAbstractListModel).begin_insert_rows(end as i32, end as i32);
-        self.list.insert(end, UrlPattern { url });
+        self.list.insert(end, UrlPattern::new(&url));
         (self as &mut dyn QAbstractListModel).end_insert_rows();
         self.count_changed();
+        self.valid_changed();
+    }
+
+    fn is_valid(&self) -> bool {
+        self.list.iter().all(UrlPattern::is_valid)
     }
 
     fn remove(&mut self, index: u64) -> bool {
@@ -186,6 +736,7 @@ impl UrlPatternsModel {
         self.list.clear();
         (self as &mut dyn QAbstractListModel).end_reset_model();
         self.count_changed();
+        self.valid_changed();
     }
 
     #[allow(non_snake_case)]
@@ -193,12 +744,27 @@ impl UrlPatternsModel {
         let s = self
             .list
             .iter()
+            .filter(|pat| !pat.url.is_empty())
             .map(|pat| pat.url.clone())
             .collect::<Vec<_>>()
             .join(",");
         QString::from(s)
     }
 
+    // Returns the row of the first pattern matching the url, or -1 if it would open in the browser
+    #[allow(non_snake_case)]
+    fn testUrl(&self, url: String) -> i32 {
+        let urls = self
+            .list
+            .iter()
+            .map(|pat| if pat.is_valid() { pat.url.as_str() } else { "" })
+            .collect::<Vec<_>>();
+        patterns::find_match(&urls, &url)
+            .filter(|idx| !urls[*idx].is_empty())
+            .map(|idx| idx as i32)
+            .unwrap_or(-1)
+    }
+
     fn insert_row(&mut self, row: usize) -> bool {
         if row > self.list.len() {
             return false;
@@ -211,13 +777,14 @@ impl UrlPatternsModel {
     }
 
     fn remove_row(&mut self, row: usize) -> bool {
-        if row > self.list.len() {
+        if row >= self.list.len() {
             return false;
         }
         (self as &mut dyn QAbstractListModel).begin_remove_rows(row as i32, row as i32);
         self.list.remove(row);
         (self as &mut dyn QAbstractListModel).end_remove_rows();
         self.count_changed();
+        self.valid_changed();
         true
     }
 }
@@ -229,11 +796,12 @@ impl QAbstractListModel for UrlPatternsModel {
 
     fn data(&self, index: QModelIndex, role: i32) -> QVariant {
         let idx = index.row() as usize;
-        if idx < self.list.len() {
-            if role == USER_ROLE {
-                QString::from(self.list[idx].url.clone()).into()
-            } else {
-                QVariant::default()
+        if let Some(pattern) = self.list.get(idx) {
+            match role {
+                PATTERN_URL_ROLE => QString::from(pattern.url.clone()).into(),
+                PATTERN_VALID_ROLE => pattern.is_valid().into(),
+                PATTERN_ERROR_ROLE => QString::from(pattern.error.clone()).into(),
+                _ => QVariant::default(),
             }
         } else {
             QVariant::default()
@@ -242,7 +810,9 @@ impl QAbstractListModel for UrlPatternsModel {
 
     fn role_names(&self) -> HashMap<i32, QByteArray> {
         let mut map = HashMap::new();
-        map.insert(USER_ROLE, "url".into());
+        map.insert(PATTERN_URL_ROLE, "url".into());
+        map.insert(PATTERN_VALID_ROLE, "valid".into());
+        map.insert(PATTERN_ERROR_ROLE, "error".into());
         map
     }
 }
@@ -0,0 +1,358 @@
+use std::cell::RefCell;
+use std::fmt;
+use std::io::{self, Read};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use reqwest::header;
+use reqwest::StatusCode;
+use url::Url;
+
+use crate::cache::{self, Response};
+
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+// Applies to the whole request, including reading the body
+const TIMEOUT: Duration = Duration::from_secs(30);
+// Pages, manifests and icons are far smaller, anything bigger is not what we are looking for
+const MAX_BODY_SIZE: u64 = 10 * 1024 * 1024;
+const MAX_REDIRECTS: usize = 10;
+const CHUNK_SIZE: usize = 16 * 1024;
+
+#[derive(Debug)]
+pub enum Error {
+    Cancelled,
+    Connect(String),
+    Dns(String),
+    HttpStatus { url: String, status: u16 },
+    NotFound { url: String, status: u16 },
+    NotHtml { url: String, content_type: String },
+    ServerError { url: String, status: u16 },
+    Timeout,
+    Tls(String),
+    TooLarge,
+    TooManyRedirects,
+    Other(String),
+}
+
+impl Error {
+    // Machine readable name of the variant, e.g. for QML
+    #[cfg(feature = "gui")]
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Error::Cancelled => "cancelled",
+            Error::Connect(_) => "connect",
+            Error::Dns(_) => "dns",
+            Error::HttpStatus { .. } => "http_status",
+            Error::NotFound { .. } => "not_found",
+            Error::NotHtml { .. } => "not_html",
+            Error::ServerError { .. } => "server_error",
+            Error::Timeout => "timeout",
+            Error::Tls(_) => "tls",
+            Error::TooLarge => "too_large",
+            Error::TooManyRedirects => "too_many_redirects",
+            Error::Other(_) => "other",
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Cancelled => write!(f, "Cancelled"),
+            Error::Connect(err) => write!(f, "The server could not be reached: {}", err),
+            Error::Dns(err) => write!(f, "The server could not be found: {}", err),
+            Error::HttpStatus { url, status } => {
+                write!(f, "The server refused to deliver {} ({})", url, status)
+            }
+            Error::NotFound { url, status } => write!(f, "{} does not exist ({})", url, status),
+            Error::NotHtml { url, content_type } => {
+                write!(f, "{} is not a web page but {}", url, content_type)
+            }
+            Error::ServerError { url, status } => {
+                write!(f, "The server failed to deliver {} ({})", url, status)
+            }
+            Error::Timeout => write!(f, "The server took too long to respond"),
+            Error::Tls(err) => write!(f, "The secure connection failed: {}", err),
+            Error::TooLarge => write!(
+                f,
+                "The response is larger than {} MiB",
+                MAX_BODY_SIZE / 1024 / 1024
+            ),
+            Error::TooManyRedirects => write!(f, "Too many redirects"),
+            Error::Other(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<String> for Error {
+    fn from(err: String) -> Self {
+        Error::Other(err)
+    }
+}
+
+// reqwest doesn't tell why connecting failed, only the messages of the underlying errors do.
+// The url it puts in front of the message is left out, it may well contain "ssl" or "tls".
+impl From<reqwest::Error> for Error {
+    fn from(err: reqwest::Error) -> Self {
+        if err.is_timeout() {
+            return Error::Timeout;
+        }
+        let message = err.to_string();
+        let mut messages = match err.url() {
+            Some(url) => message
+                .strip_prefix(&format!("{}: ", url))
+                .unwrap_or(&message)
+                .to_owned(),
+            None => message,
+        };
+        let mut source = std::error::Error::source(&err);
+        while let Some(cause) = source {
+            messages.push_str(&format!(": {}", cause));
+            source = cause.source();
+        }
+        let messages = messages.to_ascii_lowercase();
+        let contains_any = |needles: &[&str]| needles.iter().any(|n| messages.contains(n));
+        if contains_any(&[
+            "dns error",
+            "failed to lookup address",
+            "name or service not known",
+            "no address associated",
+            "temporary failure in name resolution",
+        ]) {
+            Error::Dns(err.to_string())
+        } else if contains_any(&["certificate", "ssl", "tls", "handshake"]) {
+            Error::Tls(err.to_string())
+        } else if contains_any(&[
+            "trying to connect",
+            "connection refused",
+            "connection reset",
+            "network is unreachable",
+            "no route to host",
+        ]) {
+            Error::Connect(err.to_string())
+        } else {
+            Error::Other(err.to_string())
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(err: io::Error) -> Self {
+        match err.kind() {
+            io::ErrorKind::TimedOut => Error::Timeout,
+            _ => Error::Other(err.to_string()),
+        }
+    }
+}
+
+// One scrape or download. It reports the download progress and notices when a newer
+// task of the same `Tasks` made it obsolete.
+#[derive(Clone)]
+pub struct Task {
+    id: usize,
+    latest: Arc<AtomicUsize>,
+    progress: Arc<dyn Fn(f64) + Send + Sync>,
+}
+
+impl Task {
+    // Never cancelled and without progress reporting, e.g. for the command line
+    pub fn detached() -> Self {
+        Self {
+            id: 0,
+            latest: Arc::new(AtomicUsize::new(0)),
+            progress: Arc::new(|_| {}),
+        }
+    }
+
+    #[cfg(feature = "gui")]
+    pub fn id(&self) -> usize {
+        self.id
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.latest.load(Ordering::SeqCst) != self.id
+    }
+
+    fn check(&self) -> Result<(), Error> {
+        if self.is_cancelled() {
+            Err(Error::Cancelled)
+        } else {
+            Ok(())
+        }
+    }
+
+    // Fraction of the current download, or a negative value if its size is unknown
+    fn report(&self, progress: f64) {
+        (self.progress)(progress)
+    }
+}
+
+// Hands out tasks where the latest one wins
+#[cfg(feature = "gui")]
+#[derive(Clone, Default)]
+pub struct Tasks {
+    latest: Arc<AtomicUsize>,
+}
+
+#[cfg(feature = "gui")]
+impl Tasks {
+    pub fn start<F: Fn(f64) + Send + Sync + 'static>(&self, progress: F) -> Task {
+        let id = self.latest.fetch_add(1, Ordering::SeqCst) + 1;
+        Task {
+            id,
+            latest: self.latest.clone(),
+            progress: Arc::new(progress),
+        }
+    }
+}
+
+// Remembers the user agent it sends, responses are cached per user agent
+pub struct Client {
+    inner: reqwest::Client,
+    user_agent: String,
+    // Problems that did not fail a request, e.g. a response that could not be cached
+    warnings: RefCell<Vec<String>>,
+}
+
+impl Client {
+    pub fn take_warnings(&self) -> Vec<String> {
+        self.warnings.replace(Vec::new())
+    }
+
+    fn store(&self, url: &Url, resp: &Response) {
+        if let Err(err) = cache::store(&self.user_agent, url, resp) {
+            let warning = format!("Failed to cache {}: {}", url, err);
+            self.warnings.borrow_mut().push(warning);
+        }
+    }
+}
+
+// Redirects are followed by hand in `fetch`, to know every url on the way
+pub fn client(user_agent: Option<&str>) -> Result<Client, Error> {
+    let mut builder = reqwest::Client::builder()
+        .connect_timeout(CONNECT_TIMEOUT)
+        .timeout(TIMEOUT)
+        .redirect(reqwest::RedirectPolicy::none());
+    if let Some(user_agent) = user_agent {
+        let mut headers = header::HeaderMap::new();
+        let user_agent = header::HeaderValue::from_str(user_agent)
+            .map_err(|err| format!("Invalid user agent: {}", err))?;
+        headers.insert(header::USER_AGENT, user_agent);
+        builder = builder.default_headers(headers);
+    }
+    Ok(Client {
+        inner: builder.build()?,
+        user_agent: user_agent.unwrap_or_default().to_owned(),
+        warnings: RefCell::default(),
+    })
+}
+
+// Follows redirects and returns the final response with all urls on the way
+pub fn fetch(client: &Client, url: &Url, task: &Task) -> Result<(Response, Vec<Url>), Error> {
+    let mut chain = vec![url.clone()];
+    loop {
+        let current = chain.last().unwrap().clone();
+        let resp = get(client, &current, task)?;
+        let location = match resp.location {
+            Some(ref location) if resp.is_redirection() => location,
+            _ => return Ok((resp, chain)),
+        };
+        let next = current.join(location).map_err(|err| err.to_string())?;
+        if chain.len() > MAX_REDIRECTS || chain.contains(&next) {
+            return Err(Error::TooManyRedirects);
+        }
+        chain.push(next);
+    }
+}
+
+// Turns error responses into errors, `fetch` returns them like any other response
+pub fn check_status(resp: &Response) -> Result<(), Error> {
+    let url = resp.url.clone();
+    let status = resp.status;
+    match status {
+        200..=299 => Ok(()),
+        404 | 410 => Err(Error::NotFound { url, status }),
+        500..=599 => Err(Error::ServerError { url, status }),
+        _ => Err(Error::HttpStatus { url, status }),
+    }
+}
+
+// GET request that is answered from the cache if the server says it did not change
+fn get(client: &Client, url: &Url, task: &Task) -> Result<Response, Error> {
+    task.check()?;
+    let cached = cache::load(&client.user_agent, url);
+    if let Some(ref cached) = cached {
+        if cached.is_fresh() {
+            return Ok(cached.clone());
+        }
+    }
+
+    let mut request = client.inner.get(url.as_str());
+    if let Some(ref cached) = cached {
+        if let Some(ref etag) = cached.etag {
+            request = request.header(header::IF_NONE_MATCH, etag.as_str());
+        }
+        if let Some(ref last_modified) = cached.last_modified {
+            request = request.header(header::IF_MODIFIED_SINCE, last_modified.as_str());
+        }
+    }
+
+    let mut resp = match request.send() {
+        Ok(resp) => resp,
+        Err(err) => {
+            let err = Error::from(err);
+            // Offline, the cached copy is better than nothing. Anything else, e.g. a certificate
+            // that is not trusted anymore, must not be hidden behind what was fetched before.
+            let offline = matches!(err, Error::Dns(_) | Error::Connect(_) | Error::Timeout);
+            return match cached {
+                Some(cached) if offline => Ok(cached),
+                _ => Err(err),
+            };
+        }
+    };
+
+    match cached {
+        Some(cached) if resp.status() == StatusCode::NOT_MODIFIED => {
+            let cached = cached.revalidated(&resp);
+            client.store(url, &cached);
+            Ok(cached)
+        }
+        _ => {
+            let body = read_body(&mut resp, task)?;
+            let resp_entry = Response::new(url, &resp, body);
+            if cache::is_cacheable(&resp) {
+                client.store(url, &resp_entry);
+            }
+            Ok(resp_entry)
+        }
+    }
+}
+
+// Reads the body in chunks to report progress, give up early and enforce the size limit
+fn read_body(resp: &mut reqwest::Response, task: &Task) -> Result<Vec<u8>, Error> {
+    let length = resp.content_length();
+    if length.is_some_and(|length| length > MAX_BODY_SIZE) {
+        return Err(Error::TooLarge);
+    }
+    let mut body = Vec::new();
+    let mut chunk = vec![0; CHUNK_SIZE];
+    loop {
+        task.check()?;
+        let read = resp.read(&mut chunk)?;
+        if read == 0 {
+            break;
+        }
+        body.extend_from_slice(&chunk[..read]);
+        if body.len() as u64 > MAX_BODY_SIZE {
+            return Err(Error::TooLarge);
+        }
+        match length {
+            Some(length) if length > 0 => task.report(body.len() as f64 / length as f64),
+            _ => task.report(-1.0),
+        }
+    }
+    Ok(body)
+}
@@ -0,0 +1,243 @@
+// Url patterns as understood by webapp-container's --webappUrlPatterns, e.g.
+// `https?://*.example.com/*`. The container rejects the whole list if a single
+// pattern is not considered safe, so they are checked before packaging.
+
+// Fixes common mistakes without changing what the pattern is meant to match
+pub fn normalize(pattern: &str) -> String {
+    let mut pattern = pattern.trim().to_owned();
+    if pattern.is_empty() {
+        return pattern;
+    }
+
+    for variant in &["http(s)://", "http*://", "http[s]://"] {
+        if let Some(rest) = pattern.strip_prefix(variant) {
+            pattern = format!("https?://{}", rest);
+        }
+    }
+    if !pattern.contains("://") {
+        pattern = format!("https?://{}", pattern);
+    }
+
+    let (scheme, rest) = split_scheme(&pattern);
+    let scheme = scheme.to_ascii_lowercase();
+    let (host, path) = match rest.find('/') {
+        Some(pos) => (&rest[..pos], &rest[pos..]),
+        None => (rest, "/*"),
+    };
+    let path = if path == "/" { "/*" } else { path };
+
+    format!("{}://{}{}", scheme, host.to_ascii_lowercase(), path)
+}
+
+pub fn validate(pattern: &str) -> Result<(), String> {
+    if pattern.is_empty() {
+        return Err("Pattern is empty".to_owned());
+    }
+    if pattern.contains(',') {
+        return Err("Patterns must not contain commas".to_owned());
+    }
+    if pattern.contains(char::is_whitespace) {
+        return Err("Patterns must not contain spaces".to_owned());
+    }
+    if !pattern.contains("://") {
+        return Err("Missing scheme, e.g. https?://".to_owned());
+    }
+
+    let (scheme, rest) = split_scheme(pattern);
+    match scheme {
+        "http" | "https" | "https?" => {}
+        _ => return Err(format!("Unsupported scheme \"{}\"", scheme)),
+    }
+
+    let (host, path) = match rest.find('/') {
+        Some(pos) => (&rest[..pos], &rest[pos..]),
+        None => return Err("Missing path, e.g. /*".to_owned()),
+    };
+    validate_host(host)?;
+    validate_path(path)
+}
+
+fn split_scheme(pattern: &str) -> (&str, &str) {
+    match pattern.find("://") {
+        Some(pos) => (&pattern[..pos], &pattern[pos + 3..]),
+        None => ("", pattern),
+    }
+}
+
+fn validate_host(host: &str) -> Result<(), String> {
+    let (host, port) = match host.rfind(':') {
+        Some(pos) => (&host[..pos], Some(&host[pos + 1..])),
+        None => (host, None),
+    };
+    if let Some(port) = port {
+        if port != "*" && (port.is_empty() || !port.chars().all(|c| c.is_ascii_digit())) {
+            return Err(format!("Invalid port \"{}\"", port));
+        }
+    }
+    if host.is_empty() {
+        return Err("Missing host".to_owned());
+    }
+
+    let labels = host.split('.').collect::<Vec<_>>();
+    for (i, label) in labels.iter().enumerate() {
+        if *label == "*" {
+            if i != 0 {
+                return Err("A wildcard is only allowed as the first part of the host".to_owned());
+            }
+        } else if label.contains('*') {
+            return Err(
+                "A wildcard must replace a whole part of the host, e.g. *.example.com".to_owned(),
+            );
+        } else if label.is_empty() || !label.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+        {
+            return Err(format!("Invalid host \"{}\"", host));
+        }
+    }
+    // A wildcard directly in front of the top level domain would match every site
+    if labels[0] == "*" && labels.len() < 3 {
+        return Err("The wildcard host is too broad".to_owned());
+    }
+    Ok(())
+}
+
+fn validate_path(path: &str) -> Result<(), String> {
+    if !path.starts_with('/') {
+        return Err("The path must start with /".to_owned());
+    }
+    if path.contains("**") {
+        return Err("Use a single * to match any part of the path".to_owned());
+    }
+    Ok(())
+}
+
+enum Token {
+    Literal(char),
+    Optional(char),
+    // `*` in the host, stands for a single label
+    Label,
+    Any,
+}
+
+// Mirrors how webapp-container applies patterns: `?` makes the preceding character
+// optional and `*` matches anything in the path, but only one label in the host, so
+// that `https?://*.example.com/*` can't be satisfied by the query of another site.
+// The whole url has to match.
+pub fn matches(pattern: &str, url: &str) -> bool {
+    let host = pattern.find("://").map(|pos| {
+        let start = pos + 3;
+        let end = pattern[start..]
+            .find('/')
+            .map_or(pattern.len(), |pos| start + pos);
+        start..end
+    });
+    let mut tokens = Vec::new();
+    for (i, c) in pattern.char_indices() {
+        match c {
+            '*' if host.as_ref().is_some_and(|host| host.contains(&i)) => tokens.push(Token::Label),
+            '*' => tokens.push(Token::Any),
+            '?' => match tokens.pop() {
+                Some(Token::Literal(prev)) => tokens.push(Token::Optional(prev)),
+                Some(token) => tokens.push(token),
+                None => {}
+            },
+            c => tokens.push(Token::Literal(c)),
+        }
+    }
+    let url = url.chars().collect::<Vec<_>>();
+    match_tokens(&tokens, &url)
+}
+
+fn match_tokens(tokens: &[Token], url: &[char]) -> bool {
+    match tokens.first() {
+        None => url.is_empty(),
+        Some(Token::Literal(c)) => url.first() == Some(c) && match_tokens(&tokens[1..], &url[1..]),
+        Some(Token::Optional(c)) => {
+            (url.first() == Some(c) && match_tokens(&tokens[1..], &url[1..]))
+                || match_tokens(&tokens[1..], url)
+        }
+        Some(Token::Label) => {
+            let label_len = url.iter().take_while(|c| !"./:?#@".contains(**c)).count();
+            (0..=label_len).any(|skip| match_tokens(&tokens[1..], &url[skip..]))
+        }
+        Some(Token::Any) => (0..=url.len()).any(|skip| match_tokens(&tokens[1..], &url[skip..])),
+    }
+}
+
+// Index of the first pattern the url matches, like the container checks them
+pub fn find_match<S: AsRef<str>>(patterns: &[S], url: &str) -> Option<usize> {
+    let url = url::Url::parse(url.trim())
+        .map(|url| url.to_string())
+        .unwrap_or_else(|_| url.trim().to_owned());
+    patterns
+        .iter()
+        .position(|pattern| matches(pattern.as_ref(), &url))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normalize_fixes_common_mistakes() {
+        assert_eq!(normalize(" example.com "), "https?://example.com/*");
+        assert_eq!(
+            normalize("http(s)://Example.com/"),
+            "https?://example.com/*"
+        );
+        assert_eq!(
+            normalize("http*://*.example.com"),
+            "https?://*.example.com/*"
+        );
+        assert_eq!(
+            normalize("https://example.com/app/*"),
+            "https://example.com/app/*"
+        );
+    }
+
+    #[test]
+    fn validate_rejects_unsafe_patterns() {
+        assert!(validate("https?://*.example.com/*").is_ok());
+        assert!(validate("https://example.com:8080/app/*").is_ok());
+        assert!(validate("https?://*.com/*").is_err());
+        assert!(validate("https?://www.*.com/*").is_err());
+        assert!(validate("https?://ex*.com/*").is_err());
+        assert!(validate("ftp://example.com/*").is_err());
+        assert!(validate("https://example.com").is_err());
+        assert!(validate("https://example.com/**").is_err());
+        assert!(validate("https://example.com/a,b").is_err());
+    }
+
+    #[test]
+    fn matches_scheme_and_path() {
+        assert!(matches("https?://example.com/*", "http://example.com/"));
+        assert!(matches(
+            "https?://example.com/*",
+            "https://example.com/a/b?c=d"
+        ));
+        assert!(!matches("https://example.com/*", "http://example.com/"));
+        assert!(!matches(
+            "https?://example.com/app/*",
+            "https://example.com/other"
+        ));
+    }
+
+    #[test]
+    fn host_wildcard_matches_a_single_label() {
+        let pattern = "https?://*.example.com/*";
+        assert!(matches(pattern, "https://www.example.com/"));
+        assert!(matches(pattern, "https://mail.example.com/inbox"));
+        assert!(!matches(pattern, "https://evil.com/?x=.example.com/"));
+        assert!(!matches(pattern, "https://evil.com/.example.com/"));
+        assert!(!matches(pattern, "https://a.b.example.com/"));
+        assert!(!matches(pattern, "https://user@evil.com:1@x.example.com/"));
+        assert!(!matches(pattern, "https://www.example.com.evil.com/"));
+    }
+
+    #[test]
+    fn find_match_normalizes_the_url() {
+        let patterns = ["https?://example.com/*", "https?://*.example.com/*"];
+        assert_eq!(find_match(&patterns, " https://EXAMPLE.com"), Some(0));
+        assert_eq!(find_match(&patterns, "https://www.example.com/"), Some(1));
+        assert_eq!(find_match(&patterns, "https://example.org/"), None);
+    }
+}
@@ -5,6 +5,7 @@ qrc!(qml_resources,
         "qml/App.qml",
         "qml/ContentImport.qml",
         "qml/IconButton.qml",
+        "qml/IconPickerDialog.qml",
         "qml/InstallDialog.qml",
         "qml/KeyboardPlaceholder.qml",
         "qml/Main.qml",
@@ -0,0 +1,137 @@
+use serde::Deserialize;
+use url::Url;
+
+#[derive(Deserialize, Default, Debug, Clone)]
+#[serde(default)]
+pub struct WebManifest {
+    pub name: Option<String>,
+    pub short_name: Option<String>,
+    pub theme_color: Option<String>,
+    pub background_color: Option<String>,
+    pub start_url: Option<String>,
+    pub scope: Option<String>,
+    pub icons: Vec<ManifestIcon>,
+}
+
+#[derive(Deserialize, Default, Debug, Clone)]
+#[serde(default)]
+pub struct ManifestIcon {
+    pub src: String,
+    pub sizes: Option<String>,
+    #[serde(rename = "type")]
+    pub mime_type: Option<String>,
+    pub purpose: Option<String>,
+}
+
+impl WebManifest {
+    pub fn parse(manifest_url: &Url, body: &str) -> Result<Self, String> {
+        let body = body.trim_start_matches('\u{feff}');
+        let mut manifest: Self = serde_json::from_str(body).map_err(|err| err.to_string())?;
+
+        manifest.icons.retain(|icon| !icon.src.trim().is_empty());
+
+        // All urls in the manifest are relative to the manifest itself
+        let resolve = |s: &mut String| {
+            if let Ok(url) = manifest_url.join(s.trim()) {
+                *s = url.to_string();
+            }
+        };
+        if let Some(start_url) = manifest.start_url.as_mut() {
+            resolve(start_url);
+        }
+        if let Some(scope) = manifest.scope.as_mut() {
+            resolve(scope);
+        }
+        for icon in manifest.icons.iter_mut() {
+            resolve(&mut icon.src);
+        }
+        // Icons are downloaded, a manifest must not make us read local files
+        manifest.icons.retain(|icon| {
+            Url::parse(&icon.src).is_ok_and(|url| url.scheme() == "http" || url.scheme() == "https")
+        });
+
+        Ok(manifest)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(body: &str) -> WebManifest {
+        let manifest_url = Url::parse("https://example.com/app/manifest.json").unwrap();
+        WebManifest::parse(&manifest_url, body).unwrap()
+    }
+
+    #[test]
+    fn urls_are_relative_to_the_manifest() {
+        let manifest = parse(
+            r#"{
+                "start_url": "./?source=pwa",
+                "scope": "/",
+                "icons": [
+                    {"src": "icons/192.png", "sizes": "192x192"},
+                    {"src": "/512.png"},
+                    {"src": "https://cdn.example.net/icon.svg", "type": "image/svg+xml"}
+                ]
+            }"#,
+        );
+        assert_eq!(
+            manifest.start_url.as_deref(),
+            Some("https://example.com/app/?source=pwa")
+        );
+        assert_eq!(manifest.scope.as_deref(), Some("https://example.com/"));
+        let srcs = manifest
+            .icons
+            .iter()
+            .map(|icon| icon.src.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            srcs,
+            vec![
+                "https://example.com/app/icons/192.png",
+                "https://example.com/512.png",
+                "https://cdn.example.net/icon.svg",
+            ]
+        );
+        assert_eq!(manifest.icons[0].sizes.as_deref(), Some("192x192"));
+        assert_eq!(
+            manifest.icons[2].mime_type.as_deref(),
+            Some("image/svg+xml")
+        );
+    }
+
+    #[test]
+    fn byte_order_mark_is_ignored() {
+        let manifest = parse("\u{feff}{\"name\": \"Example\"}");
+        assert_eq!(manifest.name.as_deref(), Some("Example"));
+    }
+
+    #[test]
+    fn icons_without_src_are_dropped() {
+        let manifest = parse(
+            r#"{"icons": [{"src": ""}, {"src": "  "}, {"sizes": "48x48"}, {"src": "a.png"}]}"#,
+        );
+        assert_eq!(manifest.icons.len(), 1);
+        assert_eq!(manifest.icons[0].src, "https://example.com/app/a.png");
+    }
+
+    #[test]
+    fn only_web_icons_are_kept() {
+        let manifest = parse(
+            r#"{"icons": [{"src": "file:///etc/passwd"}, {"src": "data:image/png;base64,AA=="}, {"src": "http://example.com/a.png"}]}"#,
+        );
+        let srcs = manifest
+            .icons
+            .iter()
+            .map(|icon| icon.src.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(srcs, vec!["http://example.com/a.png"]);
+    }
+
+    #[test]
+    fn invalid_json_is_an_error() {
+        let manifest_url = Url::parse("https://example.com/manifest.json").unwrap();
+        assert!(WebManifest::parse(&manifest_url, "<html>").is_err());
+    }
+}